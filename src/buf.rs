@@ -1,35 +1,709 @@
 //! Buffer for `io_uring`.
+use std::{
+    io, mem,
+    ptr::{self, NonNull},
+    slice,
+};
+
+use uring_sys2::{
+    io_uring_buf, io_uring_buf_reg, io_uring_buf_ring, io_uring_buf_ring_add,
+    io_uring_buf_ring_advance, io_uring_buf_ring_mask, io_uring_register_buf_ring,
+    io_uring_unregister_buf_ring,
+};
+
+use crate::{
+    result::{IoResult, RecvResult},
+    Error, OperationStatus, Result, Uring,
+};
 
 /// Buffer for `io_uring`.
 pub enum UringBuf {
     /// `Vec` from the standard library.
+    ///
+    /// Reads truncate it to the number of bytes read.
     Vec(Vec<u8>),
+    /// Sub-range `start..start + len` of a `Vec`.
+    ///
+    /// Operations access only the range, while the result hands back the whole `Vec`.
+    /// Reads shrink `len` to the number of bytes read.
+    VecRange {
+        vec: Vec<u8>,
+        start: usize,
+        len: usize,
+    },
     /// Unmanaged memory region.
     ///
     /// User of this library must ensure that the pointed memory region is live
-    /// until the operation completes.
+    /// until the operation completes. Reads shrink `len` to the number of bytes read.
     Raw { ptr: *mut u8, len: usize },
+    /// `Vec` whose spare capacity is uninitialized; created by [`UringBuf::uninit`].
+    ///
+    /// Like `BytesMut`, reads fill its spare capacity and advance its length by the
+    /// number of bytes read, so only the bytes the kernel wrote are exposed; writes
+    /// send its filled contents.
+    Uninit(Vec<u8>),
+    /// `BytesMut` from the `bytes` crate.
+    ///
+    /// Reads fill its spare capacity and advance its length by the number of bytes
+    /// read; writes send its filled contents.
+    #[cfg(feature = "bytes")]
+    BytesMut(bytes::BytesMut),
 }
 
 impl UringBuf {
+    /// Creates a buffer for reading up to `capacity` bytes without zeroing it first.
+    pub fn uninit(capacity: usize) -> UringBuf {
+        UringBuf::Uninit(Vec::with_capacity(capacity))
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        match self {
+            UringBuf::Vec(ref v) => v.as_ptr(),
+            UringBuf::VecRange { vec, start, .. } => vec[*start..].as_ptr(),
+            UringBuf::Raw { ptr, .. } => *ptr,
+            UringBuf::Uninit(ref v) => v.as_ptr(),
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(ref b) => b.as_ptr(),
+        }
+    }
+
     pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
         match self {
             UringBuf::Vec(ref mut v) => v.as_mut_ptr(),
+            UringBuf::VecRange { vec, start, .. } => vec[*start..].as_mut_ptr(),
             UringBuf::Raw { ptr, .. } => *ptr,
+            UringBuf::Uninit(ref mut v) => v.as_mut_ptr(),
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(ref mut b) => b.as_mut_ptr(),
+        }
+    }
+
+    /// Returns the start of the region the kernel may write into.
+    pub(crate) fn spare_ptr(&mut self) -> *mut u8 {
+        if let UringBuf::Uninit(v) = self {
+            return v.spare_capacity_mut().as_mut_ptr() as *mut u8;
+        }
+        #[cfg(feature = "bytes")]
+        if let UringBuf::BytesMut(b) = self {
+            return unsafe { b.as_mut_ptr().add(b.len()) };
+        }
+        self.as_mut_ptr()
+    }
+
+    /// Returns the length of the region the kernel may write into.
+    pub(crate) fn spare_len(&self) -> usize {
+        if let UringBuf::Uninit(v) = self {
+            return v.capacity() - v.len();
+        }
+        #[cfg(feature = "bytes")]
+        if let UringBuf::BytesMut(b) = self {
+            return b.capacity() - b.len();
+        }
+        self.len()
+    }
+
+    /// Makes the whole buffer available to the kernel again.
+    ///
+    /// A `Raw` buffer cannot grow back, so it stays at the length of its last read.
+    pub(crate) fn clear(&mut self) {
+        match self {
+            UringBuf::Vec(v) => v.resize(v.capacity(), 0),
+            UringBuf::VecRange { vec, start, len } => *len = vec.len() - *start,
+            UringBuf::Uninit(v) => v.clear(),
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(b) => b.clear(),
+            UringBuf::Raw { .. } => {}
+        }
+    }
+
+    /// Drops the first `n` bytes, e.g. the part already written.
+    pub(crate) fn advance(self, n: usize) -> UringBuf {
+        match self {
+            UringBuf::Vec(vec) => {
+                let len = vec.len() - n;
+                UringBuf::VecRange { vec, start: n, len }
+            }
+            UringBuf::VecRange { vec, start, len } => UringBuf::VecRange {
+                vec,
+                start: start + n,
+                len: len - n,
+            },
+            UringBuf::Raw { ptr, len } => UringBuf::Raw {
+                ptr: unsafe { ptr.add(n) },
+                len: len - n,
+            },
+            UringBuf::Uninit(vec) => {
+                let len = vec.len() - n;
+                UringBuf::VecRange { vec, start: n, len }
+            }
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(mut b) => {
+                bytes::Buf::advance(&mut b, n);
+                UringBuf::BytesMut(b)
+            }
+        }
+    }
+
+    /// Marks `n` bytes of the spare region as written by the kernel.
+    ///
+    /// Initialized buffers are cut down to the first `n` bytes, so that `as_slice`
+    /// returns only the data read.
+    pub(crate) fn assume_filled(&mut self, n: usize) {
+        match self {
+            UringBuf::Vec(v) => v.truncate(n),
+            UringBuf::VecRange { len, .. } | UringBuf::Raw { len, .. } => *len = n.min(*len),
+            UringBuf::Uninit(v) => unsafe { v.set_len(v.len() + n) },
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(b) => unsafe { b.set_len(b.len() + n) },
+        }
+    }
+
+    /// Rejects a `Raw` buffer that points to no memory and a `VecRange` out of bounds.
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            UringBuf::Raw { ptr, len } if ptr.is_null() || *len == 0 => {
+                Err(Error::InvalidSqe("empty raw buffer"))
+            }
+            UringBuf::VecRange { vec, start, len } if !matches!(start.checked_add(*len), Some(end) if end <= vec.len()) => {
+                Err(Error::InvalidSqe("range out of bounds"))
+            }
+            _ => Ok(()),
         }
     }
 
     pub fn as_slice(&self) -> &[u8] {
         match self {
             UringBuf::Vec(ref v) => v.as_ref(),
+            UringBuf::VecRange { vec, start, len } => &vec[*start..*start + *len],
             UringBuf::Raw { ptr, len } => unsafe { std::slice::from_raw_parts(*ptr, *len) },
+            UringBuf::Uninit(ref v) => v.as_ref(),
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(ref b) => b.as_ref(),
         }
     }
 
     pub fn len(&self) -> usize {
         match self {
             UringBuf::Vec(ref v) => v.len(),
+            UringBuf::VecRange { len, .. } => *len,
             UringBuf::Raw { len, .. } => *len,
+            UringBuf::Uninit(ref v) => v.len(),
+            #[cfg(feature = "bytes")]
+            UringBuf::BytesMut(ref b) => b.len(),
+        }
+    }
+}
+
+/// Buffer for operations where the kernel writes into the memory, such as `read(2)`.
+pub enum UringReadBuf<'a> {
+    /// Buffer owned by the operation.
+    Buf(UringBuf),
+    /// Mutably borrowed slice, created by [`UringReadBuf::from_slice`].
+    Slice(SliceMut<'a>),
+}
+
+/// Mutably borrowed slice of a [`UringReadBuf`].
+///
+/// Only [`UringReadBuf::from_slice`] creates it, so that the borrow cannot be handed to
+/// the kernel from safe code.
+pub struct SliceMut<'a>(&'a mut [u8]);
+
+impl<'a> UringReadBuf<'a> {
+    /// Lets the kernel write into `s` instead of a buffer owned by the operation.
+    ///
+    /// The handle of the operation holds the borrow until the operation completes,
+    /// and dropping the handle early waits for the completion.
+    ///
+    /// # Safety
+    ///
+    /// The handle must be dropped before the borrow ends, while the `Uring` is not in
+    /// use. Leaking it, e.g. with [`mem::forget`], or dropping it while the state of the
+    /// `Uring` is borrowed, e.g. from a waker called on a completion, defers the wait,
+    /// which lets the kernel write into `s` after the borrow ends.
+    pub unsafe fn from_slice(s: &'a mut [u8]) -> UringReadBuf<'a> {
+        UringReadBuf::Slice(SliceMut(s))
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            UringReadBuf::Buf(buf) => buf.validate(),
+            UringReadBuf::Slice(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        match self {
+            UringReadBuf::Buf(buf) => buf.spare_ptr(),
+            UringReadBuf::Slice(s) => s.0.as_mut_ptr(),
+        }
+    }
+
+    pub(crate) fn is_borrowed(&self) -> bool {
+        matches!(self, UringReadBuf::Slice(_))
+    }
+
+    /// Extends the lifetime of a borrowed slice, so that the entry of the operation can
+    /// keep it.
+    ///
+    /// # Safety
+    ///
+    /// The slice must not be used after the borrow ends. The handle of the operation
+    /// holds the borrow until the entry is removed, and the result does not hand the
+    /// slice out.
+    pub(crate) unsafe fn into_static(self) -> UringReadBuf<'static> {
+        match self {
+            UringReadBuf::Buf(buf) => UringReadBuf::Buf(buf),
+            UringReadBuf::Slice(s) => UringReadBuf::Slice(SliceMut(slice::from_raw_parts_mut(
+                s.0.as_mut_ptr(),
+                s.0.len(),
+            ))),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            UringReadBuf::Buf(buf) => buf.spare_len(),
+            UringReadBuf::Slice(s) => s.0.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Memory region given by its address and length, e.g. for `madvise(2)`.
+///
+/// Unlike [`UringBuf::Raw`], the address is a `*const u8`, so read-only memory can be
+/// passed without a mutable pointer. The kernel never writes through it. User of this
+/// library must ensure that the region is mapped until the operation completes.
+#[derive(Debug, Copy, Clone)]
+pub struct MemRegion {
+    pub addr: *const u8,
+    pub len: usize,
+}
+
+impl MemRegion {
+    pub fn new(addr: *const u8, len: usize) -> MemRegion {
+        MemRegion { addr, len }
+    }
+}
+
+/// Memory for operations where the kernel only looks at the address, such as
+/// `madvise(2)`.
+pub enum UringAdviseBuf {
+    /// Buffer owned by the operation.
+    Buf(UringBuf),
+    /// Region that is not owned and may not be writable.
+    ///
+    /// The result does not hand it out, so it can never become the target of a read.
+    Region(MemRegion),
+}
+
+impl UringAdviseBuf {
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            UringAdviseBuf::Buf(buf) => buf.validate(),
+            UringAdviseBuf::Region(region) if region.addr.is_null() || region.len == 0 => {
+                Err(Error::InvalidSqe("empty region"))
+            }
+            UringAdviseBuf::Region(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        match self {
+            UringAdviseBuf::Buf(buf) => buf.as_ptr(),
+            UringAdviseBuf::Region(region) => region.addr,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            UringAdviseBuf::Buf(buf) => buf.len(),
+            UringAdviseBuf::Region(region) => region.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<UringBuf> for UringAdviseBuf {
+    fn from(buf: UringBuf) -> Self {
+        UringAdviseBuf::Buf(buf)
+    }
+}
+
+impl From<Vec<u8>> for UringAdviseBuf {
+    fn from(vec: Vec<u8>) -> Self {
+        UringAdviseBuf::Buf(UringBuf::Vec(vec))
+    }
+}
+
+impl From<MemRegion> for UringAdviseBuf {
+    fn from(region: MemRegion) -> Self {
+        UringAdviseBuf::Region(region)
+    }
+}
+
+impl From<Vec<u8>> for UringBuf {
+    fn from(vec: Vec<u8>) -> Self {
+        UringBuf::Vec(vec)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::BytesMut> for UringBuf {
+    fn from(b: bytes::BytesMut) -> Self {
+        UringBuf::BytesMut(b)
+    }
+}
+
+impl From<UringBuf> for UringReadBuf<'static> {
+    fn from(buf: UringBuf) -> Self {
+        UringReadBuf::Buf(buf)
+    }
+}
+
+/// Read-only buffer for operations where the kernel only reads from the memory,
+/// such as `write(2)`.
+pub enum UringWriteBuf<'a> {
+    /// Buffer owned by the operation.
+    Buf(UringBuf),
+    /// Borrowed slice, created by [`UringWriteBuf::from_slice`].
+    Slice(SliceRef<'a>),
+}
+
+/// Borrowed slice of a [`UringWriteBuf`].
+///
+/// Only [`UringWriteBuf::from_slice`] creates it, like [`SliceMut`].
+pub struct SliceRef<'a>(&'a [u8]);
+
+impl<'a> UringWriteBuf<'a> {
+    /// Lets the kernel read from `s` instead of a buffer owned by the operation.
+    ///
+    /// The handle of the operation holds the borrow until the operation completes,
+    /// and dropping the handle early waits for the completion.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`UringReadBuf::from_slice`]: the handle must be dropped before the
+    /// borrow ends, while the `Uring` is not in use. Otherwise the kernel may read `s`
+    /// after its memory is reused.
+    pub unsafe fn from_slice(s: &'a [u8]) -> UringWriteBuf<'a> {
+        UringWriteBuf::Slice(SliceRef(s))
+    }
+
+    /// Returns the borrowed slice, if any.
+    pub(crate) fn as_slice(&self) -> Option<&'a [u8]> {
+        match self {
+            UringWriteBuf::Buf(_) => None,
+            UringWriteBuf::Slice(s) => Some(s.0),
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        match self {
+            UringWriteBuf::Buf(buf) => buf.validate(),
+            UringWriteBuf::Slice(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        match self {
+            UringWriteBuf::Buf(buf) => buf.as_ptr(),
+            UringWriteBuf::Slice(s) => s.0.as_ptr(),
+        }
+    }
+
+    pub(crate) fn is_borrowed(&self) -> bool {
+        matches!(self, UringWriteBuf::Slice(_))
+    }
+
+    /// Extends the lifetime of a borrowed slice, like [`UringReadBuf::into_static`].
+    ///
+    /// # Safety
+    ///
+    /// The slice must not be used after the borrow ends.
+    pub(crate) unsafe fn into_static(self) -> UringWriteBuf<'static> {
+        match self {
+            UringWriteBuf::Buf(buf) => UringWriteBuf::Buf(buf),
+            UringWriteBuf::Slice(s) => {
+                UringWriteBuf::Slice(SliceRef(slice::from_raw_parts(s.0.as_ptr(), s.0.len())))
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            UringWriteBuf::Buf(buf) => buf.len(),
+            UringWriteBuf::Slice(s) => s.0.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<UringBuf> for UringWriteBuf<'static> {
+    fn from(buf: UringBuf) -> Self {
+        UringWriteBuf::Buf(buf)
+    }
+}
+
+/// Buffer kept by the result of an operation.
+///
+/// A borrowed slice is not handed out by the result, as the borrow may end as soon as
+/// the handle is consumed; only its length is kept. Neither is a [`MemRegion`].
+pub(crate) enum ResultBuf {
+    Owned(UringBuf),
+    Borrowed(usize),
+}
+
+impl ResultBuf {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            ResultBuf::Owned(buf) => buf.len(),
+            ResultBuf::Borrowed(len) => *len,
+        }
+    }
+
+    /// Returns the owned buffer, or an empty `Vec` for a borrowed slice.
+    pub(crate) fn into_buf(self) -> UringBuf {
+        self.into_owned().unwrap_or(UringBuf::Vec(Vec::new()))
+    }
+
+    pub(crate) fn into_owned(self) -> Option<UringBuf> {
+        match self {
+            ResultBuf::Owned(buf) => Some(buf),
+            ResultBuf::Borrowed(_) => None,
+        }
+    }
+}
+
+impl From<UringBuf> for ResultBuf {
+    fn from(buf: UringBuf) -> Self {
+        ResultBuf::Owned(buf)
+    }
+}
+
+impl From<UringReadBuf<'static>> for ResultBuf {
+    fn from(buf: UringReadBuf<'static>) -> Self {
+        match buf {
+            UringReadBuf::Buf(buf) => ResultBuf::Owned(buf),
+            UringReadBuf::Slice(s) => ResultBuf::Borrowed(s.0.len()),
+        }
+    }
+}
+
+impl From<UringAdviseBuf> for ResultBuf {
+    fn from(buf: UringAdviseBuf) -> Self {
+        match buf {
+            UringAdviseBuf::Buf(buf) => ResultBuf::Owned(buf),
+            UringAdviseBuf::Region(region) => ResultBuf::Borrowed(region.len),
+        }
+    }
+}
+
+impl From<UringWriteBuf<'static>> for ResultBuf {
+    fn from(buf: UringWriteBuf<'static>) -> Self {
+        match buf {
+            UringWriteBuf::Buf(buf) => ResultBuf::Owned(buf),
+            UringWriteBuf::Slice(s) => ResultBuf::Borrowed(s.0.len()),
+        }
+    }
+}
+
+/// Ring of buffers provided to the kernel, registered with `io_uring_register_buf_ring`.
+///
+/// Operations with buffer selection, such as [`RecvData`](crate::sqe::RecvData), let the
+/// kernel pick a buffer from the ring, and the result reports which one was used. Give
+/// the buffer back with [`recycle`](BufRing::recycle) once its data is consumed;
+/// buffers of dropped results are not returned to the kernel.
+pub struct BufRing<'a> {
+    ring: &'a Uring,
+    br: NonNull<io_uring_buf_ring>,
+    bufs: NonNull<u8>,
+    entries: u16,
+    buf_len: usize,
+    bgid: u16,
+}
+
+impl<'a> BufRing<'a> {
+    pub(crate) fn new(
+        ring: &'a Uring,
+        entries: u16,
+        buf_len: usize,
+        bgid: u16,
+    ) -> Result<BufRing<'a>> {
+        // The kernel rejects other sizes, and the mask of a ring assumes a power of two.
+        // The largest power of two in a `u16`, 32768, is also the limit of the kernel.
+        if !entries.is_power_of_two() {
+            return Err(Error::InvalidSqe(
+                "buffer ring entries must be a power of two up to 32768",
+            ));
+        }
+        let br = unsafe {
+            let br = libc::mmap(
+                ptr::null_mut(),
+                Self::ring_size(entries),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if br == libc::MAP_FAILED {
+                return Err(Error::RegisterBufRingError(io::Error::last_os_error()));
+            }
+            NonNull::new_unchecked(br as *mut io_uring_buf_ring)
+        };
+
+        unsafe {
+            let mut reg: io_uring_buf_reg = mem::zeroed();
+            reg.ring_addr = br.as_ptr() as u64;
+            reg.ring_entries = entries as u32;
+            reg.bgid = bgid;
+            let ret = io_uring_register_buf_ring(ring.ring.get(), &mut reg, 0);
+            if ret < 0 {
+                libc::munmap(br.as_ptr() as *mut _, Self::ring_size(entries));
+                return Err(Error::RegisterBufRingError(io::Error::from_raw_os_error(
+                    -ret,
+                )));
+            }
+        }
+
+        let bufs = vec![0u8; entries as usize * buf_len].into_boxed_slice();
+        let buf_ring = BufRing {
+            ring,
+            br,
+            bufs: unsafe { NonNull::new_unchecked(Box::into_raw(bufs) as *mut u8) },
+            entries,
+            buf_len,
+            bgid,
+        };
+        unsafe {
+            for bid in 0..entries {
+                buf_ring.add(bid);
+            }
+            io_uring_buf_ring_advance(br.as_ptr(), entries as i32);
+        }
+        Ok(buf_ring)
+    }
+
+    /// Returns the buffer group id of the ring.
+    pub fn bgid(&self) -> u16 {
+        self.bgid
+    }
+
+    /// Returns the data received into the buffer selected for `result`.
+    ///
+    /// Returns `None` if the operation failed or did not select a buffer from this ring.
+    pub fn get<'r>(&'r self, result: &'r RecvResult) -> Option<&'r [u8]> {
+        let bid = self.selected(result)?;
+        let len = result.as_io_result().ok()?;
+        unsafe { Some(slice::from_raw_parts(self.buf_ptr(bid), len)) }
+    }
+
+    /// Gives the buffer selected for `result` back to the kernel.
+    pub fn recycle(&self, result: RecvResult) {
+        if let Some(bid) = self.selected(&result) {
+            unsafe {
+                self.add(bid);
+                io_uring_buf_ring_advance(self.br.as_ptr(), 1);
+            }
+        }
+    }
+
+    fn selected(&self, result: &RecvResult) -> Option<u16> {
+        if result.bgid() == self.bgid {
+            result.buffer_id()
+        } else {
+            None
+        }
+    }
+
+    fn ring_size(entries: u16) -> usize {
+        entries as usize * mem::size_of::<io_uring_buf>()
+    }
+
+    fn buf_ptr(&self, bid: u16) -> *mut u8 {
+        unsafe { self.bufs.as_ptr().add(bid as usize * self.buf_len) }
+    }
+
+    /// Cancels the operations that may still select a buffer from this ring and waits
+    /// for their last completions, returning `false` if that failed.
+    ///
+    /// Dropping a handle already does so, but a leaked one, e.g. with `mem::forget`,
+    /// leaves its operation in flight after the borrow of the ring ends.
+    fn quiesce(&self) -> bool {
+        let mut context = match self.ring.context() {
+            Ok(context) => context,
+            Err(_) => return false,
+        };
+        let ids = context
+            .state
+            .map
+            .iter()
+            .filter(|(_, op)| {
+                op.kind.buf_group() == Some(self.bgid)
+                    && (matches!(
+                        op.status,
+                        OperationStatus::Ongoing | OperationStatus::Cancelled
+                    ) || op.is_armed_multishot())
+            })
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        for id in ids {
+            // Nobody holds the handle anymore; `handle_cqe` removes the entry with the
+            // last completion.
+            if let Some(op) = context.state.map.get_mut(id) {
+                if let OperationStatus::Multishot(completions) = &op.status {
+                    for (res, _) in completions {
+                        op.kind.discard(*res);
+                    }
+                }
+                op.status = OperationStatus::Cancelled;
+            }
+            if self.ring.cancel(&mut context, id).is_err() {
+                return false;
+            }
+            while context.state.map.contains_key(id) {
+                if self.ring.wait_for(&mut context, id).is_err() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    unsafe fn add(&self, bid: u16) {
+        io_uring_buf_ring_add(
+            self.br.as_ptr(),
+            self.buf_ptr(bid) as *mut _,
+            self.buf_len as u32,
+            bid,
+            io_uring_buf_ring_mask(self.entries as u32),
+            0,
+        );
+    }
+}
+
+impl<'a> Drop for BufRing<'a> {
+    fn drop(&mut self) {
+        if !self.quiesce() {
+            // The kernel may still write into the buffers; leak them instead.
+            return;
+        }
+        unsafe {
+            io_uring_unregister_buf_ring(self.ring.ring.get(), self.bgid as i32);
+            libc::munmap(self.br.as_ptr() as *mut _, Self::ring_size(self.entries));
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.bufs.as_ptr(),
+                self.entries as usize * self.buf_len,
+            )));
         }
     }
 }
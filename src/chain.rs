@@ -0,0 +1,103 @@
+//! Linked sequence of operations submitted together.
+use uring_sys2::{IOSQE_IO_HARDLINK, IOSQE_IO_LINK};
+
+use crate::{
+    handle::UringHandle,
+    sqe::{Sqe, UringSqe},
+    Error, Result, Uring,
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// [`Sqe`] that can be part of a [`Chain`].
+///
+/// Implemented for the `Sqe` of every single-shot operation.
+pub trait ChainSqe<'a>: private::Sealed {
+    #[doc(hidden)]
+    fn validate_linked(&self) -> Result<()>;
+
+    #[doc(hidden)]
+    fn prepare_linked(self: Box<Self>, ring: &'a Uring, flag: u32) -> Result<UringHandle<'a>>;
+}
+
+impl<T> private::Sealed for Sqe<T> {}
+
+impl<'a, T> ChainSqe<'a> for Sqe<T>
+where
+    Sqe<T>: UringSqe<'a>,
+    <Sqe<T> as UringSqe<'a>>::Handle: Into<UringHandle<'a>>,
+{
+    fn validate_linked(&self) -> Result<()> {
+        self.validate()
+    }
+
+    fn prepare_linked(self: Box<Self>, ring: &'a Uring, flag: u32) -> Result<UringHandle<'a>> {
+        let mut sqe = *self;
+        sqe.flag |= flag;
+        ring.prepare(&mut ring.context()?, sqe).map(Into::into)
+    }
+}
+
+/// Builder of a linked sequence of operations, created by [`Uring::chain`].
+///
+/// Every operation but the last is linked to the next one, so that each starts only
+/// after the previous one succeeded. The SQEs are placed next to each other in the
+/// SQ; no other SQE can end up in the middle of the chain.
+pub struct Chain<'a> {
+    ring: &'a Uring,
+    entries: Vec<Box<dyn ChainSqe<'a> + 'a>>,
+    link: u32,
+}
+
+impl<'a> Chain<'a> {
+    pub(crate) fn new(ring: &'a Uring) -> Chain<'a> {
+        Chain {
+            ring,
+            entries: Vec::new(),
+            link: IOSQE_IO_LINK,
+        }
+    }
+
+    /// Uses hard links (`IOSQE_IO_HARDLINK`), so that the next operation starts even
+    /// if the previous one failed.
+    pub fn hard(mut self) -> Chain<'a> {
+        self.link = IOSQE_IO_HARDLINK;
+        self
+    }
+
+    /// Appends an operation to the chain.
+    pub fn push<S>(mut self, entry: S) -> Chain<'a>
+    where
+        S: ChainSqe<'a> + 'a,
+    {
+        self.entries.push(Box::new(entry));
+        self
+    }
+
+    /// Prepares all operations of the chain and returns their handles in order.
+    ///
+    /// Fails without preparing anything if an operation is invalid or the chain does
+    /// not fit in the SQ. If the SQ does not have enough free entries, the queued
+    /// SQEs are handled according to the [`SqFullPolicy`](crate::SqFullPolicy) first.
+    pub fn prepare(self) -> Result<Vec<UringHandle<'a>>> {
+        let n = self.entries.len();
+        if n > self.ring.sq_space_left() + self.ring.sq_ready() {
+            return Err(Error::InvalidSqe("chain longer than the SQ"));
+        }
+        for entry in &self.entries {
+            entry.validate_linked()?;
+        }
+        if self.ring.sq_space_left() < n {
+            self.ring.make_room(&mut self.ring.context()?)?;
+        }
+
+        let (ring, link) = (self.ring, self.link);
+        self.entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| entry.prepare_linked(ring, if i + 1 < n { link } else { 0 }))
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+//! Bridge from handles to `Future`s, enabled by the `async` feature.
+//!
+//! The futures do not drive the ring. Something else, e.g. a loop calling
+//! [`Uring::submit`](crate::Uring::submit) and [`Uring::completions`](crate::Uring::completions),
+//! must submit the operations and process their completions; processing a
+//! completion wakes the task waiting on it.
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    handle::{Handler, Wait},
+    Result,
+};
+
+/// Future resolving to the result of a handle, created by e.g.
+/// [`ReadHandle::into_future`](crate::handle::ReadHandle::into_future).
+#[derive(Debug)]
+pub struct UringFuture<H> {
+    handle: Option<H>,
+}
+
+impl<H> UringFuture<H> {
+    pub(crate) fn new(handle: H) -> UringFuture<H> {
+        UringFuture {
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<'a, H> Future for UringFuture<H>
+where
+    H: Handler<'a> + Wait + Unpin,
+{
+    type Output = Result<<H as Wait>::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let handle = self
+            .handle
+            .as_ref()
+            .expect("UringFuture polled after completion");
+        if !handle.handle().poll_observed(cx.waker()) {
+            return Poll::Pending;
+        }
+        // The result is observed, so waiting does not block.
+        let handle = self.handle.take().unwrap();
+        Poll::Ready(handle.wait())
+    }
+}
@@ -1,36 +1,139 @@
 //! Handle for an ongoing or completed io_uring operation.
-use std::collections::hash_map::Entry;
+use std::{fmt, os::unix::io::RawFd};
 
-use crate::{result::*, OperationStatus, Result, Uring, UringOperation, UringOperationKind};
+use uring_sys2::IORING_CQE_F_MORE;
 
-pub(crate) trait Handler<'a>: Into<UringHandle<'a>> {
+#[cfg(feature = "async")]
+use crate::future::UringFuture;
+use crate::{
+    buf::UringWriteBuf, result::*, sqe::Sqe, Error, OperationId, OperationStatus, Result, Uring,
+    UringContext, UringOperation, UringOperationKind,
+};
+
+/// Handle of an operation whose result can be waited for.
+///
+/// Used by [`Uring::wait_all`](crate::Uring::wait_all) to wait on handles of any type.
+pub trait Wait {
+    type Output;
+
+    /// Waits for the asynchronous operation and returns its result.
+    fn wait(self) -> Result<Self::Output>;
+}
+
+pub(crate) trait Handler<'a> {
     type Output;
 
     fn new(id: u64, ring: &'a Uring) -> Self;
+
+    fn handle(&self) -> &Handle<'a>;
 }
 
 macro_rules! define_handle {
     ($([$var:ident, $h:ident, $result:ident, $doc:expr],)*) => {
+        /// Kind of an operation, returned by the `kind` method of the handles.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum OperationKind {
+            $($var,)*
+            MultishotAccept,
+            MultishotRecv,
+        }
+
         /// Generalized `Uring` operation handler.
+        #[derive(Debug)]
         pub enum UringHandle<'a> {
             $(
                 #[doc = $doc]
                 $var($h<'a>),
             )*
         }
+        impl<'a> Wait for UringHandle<'a> {
+            type Output = UringResult;
+            fn wait(self) -> Result<UringResult> {
+                UringHandle::wait(self)
+            }
+        }
+        impl<'a> UringHandle<'a> {
+            /// Waits for the asynchronous operation and returns its result.
+            pub fn wait(self) -> Result<UringResult> {
+                match self {
+                    $(UringHandle::$var(h) => h.wait().map(Into::into),)*
+                }
+            }
+
+            /// Returns the id of the operation, as yielded by
+            /// [`Uring::completions`](crate::Uring::completions).
+            pub fn id(&self) -> OperationId {
+                match self {
+                    $(UringHandle::$var(h) => h.id(),)*
+                }
+            }
+
+            /// Returns the kind of the operation.
+            pub fn kind(&self) -> OperationKind {
+                match self {
+                    $(UringHandle::$var(_) => OperationKind::$var,)*
+                }
+            }
+        }
         $(
             #[doc = $doc]
+            #[derive(Debug)]
             pub struct $h<'a>(Handle<'a>);
             impl<'a> $h<'a> {
                 /// Waits for the asynchronous operation and returns its handle.
                 pub fn wait(self) -> Result<$result> {
-                    self.0.wait()?.try_into()
+                    let tag = self.0.tag()?;
+                    let result: $result = self.0.wait()?.try_into()?;
+                    Ok(result.with_tag(tag))
+                }
+
+                /// Returns the result if the operation is completed, without blocking.
+                ///
+                /// The completions ready in the CQ are processed first. Otherwise, the
+                /// handle stays usable; once the result is returned, waiting on the
+                /// handle fails with [`Error::MissingOperation`].
+                pub fn try_result(&mut self) -> Result<Option<$result>> {
+                    let tag = self.0.tag()?;
+                    match self.0.try_take()? {
+                        Some(completed) => {
+                            let result: $result = completed.try_into()?;
+                            Ok(Some(result.with_tag(tag)))
+                        }
+                        None => Ok(None),
+                    }
+                }
+
+                /// Waits for the asynchronous operation and returns the raw `res` of its
+                /// completion, dropping what the operation owned.
+                pub fn wait_raw(self) -> Result<i32> {
+                    self.0.wait().map(|(res, _, _)| res)
                 }
 
                 /// Returns true if the result is already observed.
                 pub fn observed(&self) -> bool {
                     self.0.observed()
                 }
+
+                /// Returns the id of the operation, as yielded by
+                /// [`Uring::completions`](crate::Uring::completions).
+                pub fn id(&self) -> OperationId {
+                    OperationId(self.0.id)
+                }
+
+                /// Returns the kind of the operation.
+                pub fn kind(&self) -> OperationKind {
+                    OperationKind::$var
+                }
+
+                /// Converts the handle into a future resolving to the result.
+                ///
+                /// The future does not drive the ring: the operation must be submitted
+                /// and its completion processed elsewhere, e.g. with
+                /// [`Uring::completions`](crate::Uring::completions).
+                #[cfg(feature = "async")]
+                pub fn into_future(self) -> UringFuture<Self> {
+                    UringFuture::new(self)
+                }
             }
             impl<'a> Into<UringHandle<'a>> for $h<'a> {
                 fn into(self) -> UringHandle<'a> {
@@ -42,6 +145,15 @@ macro_rules! define_handle {
                 fn new(id: u64, ring: &'a Uring) -> Self {
                     $h(Handle::new(id, ring))
                 }
+                fn handle(&self) -> &Handle<'a> {
+                    &self.0
+                }
+            }
+            impl<'a> Wait for $h<'a> {
+                type Output = $result;
+                fn wait(self) -> Result<$result> {
+                    $h::wait(self)
+                }
             }
         )*
     }
@@ -63,74 +175,509 @@ define_handle!(
         MadviseResult,
         "Handler for `madvise`."
     ],
+    [
+        Fadvise,
+        FadviseHandle,
+        FadviseResult,
+        "Handler for `fadvise`."
+    ],
+    [
+        Ftruncate,
+        FtruncateHandle,
+        FtruncateResult,
+        "Handler for `ftruncate`."
+    ],
+    [
+        Shutdown,
+        ShutdownHandle,
+        ShutdownResult,
+        "Handler for `shutdown`."
+    ],
+    [Socket, SocketHandle, SocketResult, "Handler for `socket`."],
+    [Close, CloseHandle, CloseResult, "Handler for `close`."],
+    [Recv, RecvHandle, RecvResult, "Handler for `recv`."],
+    [
+        Sendmsg,
+        SendmsgHandle,
+        SendmsgResult,
+        "Handler for `sendmsg`."
+    ],
+    [
+        Recvmsg,
+        RecvmsgHandle,
+        RecvmsgResult,
+        "Handler for `recvmsg`."
+    ],
+    [
+        Rename,
+        RenameHandle,
+        RenameResult,
+        "Handler for `renameat2`."
+    ],
+    [
+        Unlink,
+        UnlinkHandle,
+        UnlinkResult,
+        "Handler for `unlinkat`."
+    ],
+    [Mkdir, MkdirHandle, MkdirResult, "Handler for `mkdirat`."],
+    [
+        Symlink,
+        SymlinkHandle,
+        SymlinkResult,
+        "Handler for `symlinkat`."
+    ],
+    [Link, LinkHandle, LinkResult, "Handler for `linkat`."],
+    [Nop, NopHandle, NopResult, "Handler for `nop`."],
+    [Splice, SpliceHandle, SpliceResult, "Handler for `splice`."],
+    [Tee, TeeHandle, TeeResult, "Handler for `tee`."],
+    [
+        MsgRingReceive,
+        MsgRingReceiveHandle,
+        MsgRingReceiveResult,
+        "Handler for a message from another ring."
+    ],
+    [
+        MsgRing,
+        MsgRingHandle,
+        MsgRingResult,
+        "Handler for `msg_ring`."
+    ],
+    [
+        EpollCtl,
+        EpollCtlHandle,
+        EpollCtlResult,
+        "Handler for `epoll_ctl`."
+    ],
+    [Waitid, WaitidHandle, WaitidResult, "Handler for `waitid`."],
+    [
+        Setxattr,
+        SetxattrHandle,
+        SetxattrResult,
+        "Handler for `setxattr`."
+    ],
+    [
+        Getxattr,
+        GetxattrHandle,
+        GetxattrResult,
+        "Handler for `getxattr`."
+    ],
+    [
+        SyncFileRange,
+        SyncFileRangeHandle,
+        SyncFileRangeResult,
+        "Handler for `sync_file_range`."
+    ],
+    [
+        SendZc,
+        SendZcHandle,
+        SendZcResult,
+        "Handle of asynchronous zero-copy `send(2)`."
+    ],
+    [Openat, OpenatHandle, OpenatResult, "Handler for `openat`."],
+    [Bind, BindHandle, BindResult, "Handler for `bind`."],
+    [Listen, ListenHandle, ListenResult, "Handler for `listen`."],
 );
 
+/// Handler for multishot `accept`.
+///
+/// Dropping the handle cancels the operation if it is still armed.
+#[derive(Debug)]
+pub struct MultishotAcceptHandle<'a>(Handle<'a>);
+
+impl<'a> MultishotAcceptHandle<'a> {
+    /// Waits for the next accepted connection.
+    ///
+    /// Returns `None` once the operation has terminated and all of its results are taken.
+    pub fn wait(&mut self) -> Result<Option<AcceptResult>> {
+        let tag = self.0.tag()?;
+        Ok(self
+            .0
+            .wait_next()?
+            .map(|(res, flags)| AcceptResult::new(res, flags).with_tag(tag)))
+    }
+
+    /// Returns true if a result is already observed and not yet taken.
+    pub fn observed(&self) -> bool {
+        self.0.observed()
+    }
+
+    /// Returns the id of the operation, as yielded by
+    /// [`Uring::completions`](crate::Uring::completions).
+    pub fn id(&self) -> OperationId {
+        OperationId(self.0.id)
+    }
+
+    /// Returns the kind of the operation.
+    pub fn kind(&self) -> OperationKind {
+        OperationKind::MultishotAccept
+    }
+}
+
+impl<'a> Handler<'a> for MultishotAcceptHandle<'a> {
+    type Output = AcceptResult;
+    fn new(id: u64, ring: &'a Uring) -> Self {
+        MultishotAcceptHandle(Handle::new(id, ring))
+    }
+    fn handle(&self) -> &Handle<'a> {
+        &self.0
+    }
+}
+
+/// Handler for multishot `recv`.
+///
+/// Dropping the handle cancels the operation if it is still armed and waits for its
+/// last completion, as the kernel may select buffers from the borrowed ring until then.
+#[derive(Debug)]
+pub struct MultishotRecvHandle<'a>(Handle<'a>);
+
+impl<'a> MultishotRecvHandle<'a> {
+    /// Waits for the next received chunk of data.
+    ///
+    /// Returns `None` once the operation has terminated and all of its results are taken.
+    pub fn wait(&mut self) -> Result<Option<RecvResult>> {
+        let tag = self.0.tag()?;
+        let bgid = match self.0.kind_of(|kind| match kind {
+            UringOperationKind::MultishotRecv { bgid } => *bgid,
+            _ => unreachable!("multishot recv handle of another operation"),
+        })? {
+            Some(bgid) => bgid,
+            None => return Ok(None),
+        };
+        Ok(self
+            .0
+            .wait_next()?
+            .map(|(res, flags)| RecvResult::new(res, flags, bgid).with_tag(tag)))
+    }
+
+    /// Returns true if a result is already observed and not yet taken.
+    pub fn observed(&self) -> bool {
+        self.0.observed()
+    }
+
+    /// Returns the id of the operation, as yielded by
+    /// [`Uring::completions`](crate::Uring::completions).
+    pub fn id(&self) -> OperationId {
+        OperationId(self.0.id)
+    }
+
+    /// Returns the kind of the operation.
+    pub fn kind(&self) -> OperationKind {
+        OperationKind::MultishotRecv
+    }
+}
+
+impl<'a> Handler<'a> for MultishotRecvHandle<'a> {
+    type Output = RecvResult;
+    fn new(id: u64, ring: &'a Uring) -> Self {
+        MultishotRecvHandle(Handle::new(id, ring))
+    }
+    fn handle(&self) -> &Handle<'a> {
+        &self.0
+    }
+}
+
+/// Handler for `write` that retries short writes, created by
+/// [`Uring::prepare_write_all`](crate::Uring::prepare_write_all).
+#[derive(Debug)]
+pub struct WriteAllHandle<'a> {
+    handle: WriteHandle<'a>,
+    ring: &'a Uring,
+    fd: RawFd,
+    offset: u64,
+    /// The slice borrowed by the first write, whose tail the retries write.
+    slice: Option<&'a [u8]>,
+    fd_borrowed: bool,
+}
+
+impl<'a> WriteAllHandle<'a> {
+    pub(crate) fn new(
+        handle: WriteHandle<'a>,
+        ring: &'a Uring,
+        fd: RawFd,
+        offset: u64,
+        slice: Option<&'a [u8]>,
+        fd_borrowed: bool,
+    ) -> WriteAllHandle<'a> {
+        WriteAllHandle {
+            handle,
+            ring,
+            fd,
+            offset,
+            slice,
+            fd_borrowed,
+        }
+    }
+
+    /// Waits until the whole buffer is written or a write fails.
+    ///
+    /// On success, the result reports the total number of bytes written, and its buffer
+    /// is the one of the last write, i.e. without the part written before; a `Vec`
+    /// comes back as [`UringBuf::VecRange`](crate::buf::UringBuf::VecRange). If the
+    /// first write fails, the result carries its error. Any failure after a part of the
+    /// buffer is written returns [`Error::WriteAllError`] with the number of bytes
+    /// written.
+    ///
+    /// Each retry is submitted with [`Uring::submit`], which also submits the SQEs
+    /// prepared but not submitted yet.
+    pub fn wait(self) -> Result<WriteResult> {
+        let WriteAllHandle {
+            mut handle,
+            ring,
+            fd,
+            mut offset,
+            slice,
+            fd_borrowed,
+        } = self;
+        // The retries carry the tag of the first write.
+        let tag = handle.0.tag()?;
+        let mut written = 0;
+        loop {
+            let mut result = handle.wait().map_err(|e| partial(written, e))?;
+            let n = result.transferred();
+            written += n;
+            if !result.is_short() || n == 0 {
+                match result.as_io_result() {
+                    Ok(_) => result.set_transferred(written),
+                    Err(source) if written > 0 => {
+                        return Err(Error::WriteAllError { written, source })
+                    }
+                    Err(_) => {}
+                }
+                return Ok(result);
+            }
+
+            // `u64::MAX` writes at the current file position, which advances by itself.
+            if offset != u64::MAX {
+                offset += n as u64;
+            }
+            let tail = match slice {
+                // The caller of `from_slice` promised not to leak this handle, which
+                // holds the retries like the first write.
+                Some(s) => unsafe { UringWriteBuf::from_slice(&s[written..]) },
+                None => UringWriteBuf::Buf(result.into_buf().advance(n)),
+            };
+            handle = ring
+                .prepare_write(Sqe {
+                    fd_borrowed,
+                    tag,
+                    ..Sqe::write(fd, tail, offset)
+                })
+                .map_err(|e| partial(written, e))?;
+            ring.submit().map_err(|e| partial(written, e))?;
+        }
+    }
+}
+
+/// Attaches the number of bytes already written by a `write_all` to `e`.
+fn partial(written: usize, e: Error) -> Error {
+    if written == 0 {
+        return e;
+    }
+    Error::WriteAllError {
+        written,
+        source: e.into(),
+    }
+}
+
+impl<'a> Wait for WriteAllHandle<'a> {
+    type Output = WriteResult;
+    fn wait(self) -> Result<WriteResult> {
+        WriteAllHandle::wait(self)
+    }
+}
+
 /// General handle for `Uring` operations.
 pub(crate) struct Handle<'a> {
     id: u64,
     ring: &'a Uring,
 }
 
+impl<'a> fmt::Debug for Handle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("id", &self.id)
+            .field("observed", &self.observed())
+            .finish()
+    }
+}
+
 impl<'a> Handle<'a> {
     pub(crate) fn new(id: u64, ring: &'a Uring) -> Handle<'a> {
         Handle { id, ring }
     }
 
+    /// Returns true if the result is observed or the operation is gone, so that
+    /// `wait` does not block. Otherwise, `waker` is woken on the next completion.
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_observed(&self, waker: &std::task::Waker) -> bool {
+        if self.observed() {
+            return true;
+        }
+        let mut context = match self.ring.context() {
+            Ok(context) => context,
+            // Let `wait` report the error.
+            Err(_) => return true,
+        };
+        if !context.state.map.contains_key(self.id) {
+            return true;
+        }
+        context.state.wakers.insert(self.id, waker.clone());
+        false
+    }
+
+    /// Returns the tag attached to the operation, if it is still in the map.
+    ///
+    /// Fails with [`Error::Busy`] like the other accesses to the state.
+    fn tag(&self) -> Result<Option<u64>> {
+        let state = self.ring.state.try_borrow().map_err(|_| Error::Busy)?;
+        Ok(state.map.get(self.id).and_then(|op| op.tag))
+    }
+
+    /// Applies `f` to the kind of the operation, if it is still in the map.
+    fn kind_of<T>(&self, f: impl FnOnce(&UringOperationKind) -> T) -> Result<Option<T>> {
+        let state = self.ring.state.try_borrow().map_err(|_| Error::Busy)?;
+        Ok(state.map.get(self.id).map(|op| f(&op.kind)))
+    }
+
+    /// Returns false while the state is borrowed, as nothing can be observed then.
     fn observed(&self) -> bool {
-        self.ring
-            .state
-            .borrow()
+        let state = match self.ring.state.try_borrow() {
+            Ok(state) => state,
+            Err(_) => return false,
+        };
+        state
             .map
-            .get(&self.id)
-            .map(|e| match e.status {
-                OperationStatus::Completed(_) => true,
+            .get(self.id)
+            .map(|e| match &e.status {
+                OperationStatus::Completed(..) => true,
+                OperationStatus::Multishot(completions) => !completions.is_empty(),
                 _ => false,
             })
             .unwrap_or(false)
     }
 
-    fn wait(self) -> Result<(i32, UringOperationKind)> {
-        let mut context = self.ring.context();
-        match context.state.map.entry(self.id) {
-            Entry::Occupied(op) => match op.get() {
-                UringOperation {
-                    status: OperationStatus::Completed(res),
+    fn wait(self) -> Result<(i32, u32, UringOperationKind)> {
+        let mut context = self.ring.context()?;
+        if let Some(completed) = context.state.take_completed(self.id) {
+            return Ok(completed);
+        }
+        if !context.state.map.contains_key(self.id) {
+            return Err(Error::MissingOperation(self.id));
+        }
+        self.ring.wait_completed(&mut context, self.id)?;
+        // An entry that is still not completed is kept so that the operation can
+        // still complete.
+        context
+            .state
+            .take_completed(self.id)
+            .ok_or(Error::MissingOperation(self.id))
+    }
+
+    /// Takes the result if the operation is completed, processing the completions ready
+    /// in the CQ without waiting.
+    fn try_take(&self) -> Result<Option<(i32, u32, UringOperationKind)>> {
+        let mut context = self.ring.context()?;
+        while let Some(cqe) = self.ring.peek_cqe() {
+            self.ring.handle_cqe(&mut context, cqe)?;
+        }
+        if !context.state.map.contains_key(self.id) {
+            return Err(Error::MissingOperation(self.id));
+        }
+        Ok(context.state.take_completed(self.id))
+    }
+
+    /// Takes the next result of a multishot operation, waiting if none is observed yet.
+    ///
+    /// Each result comes with the flags of its completion.
+    fn wait_next(&self) -> Result<Option<(i32, u32)>> {
+        let mut context = self.ring.context()?;
+        loop {
+            let next = match context.state.map.get_mut(self.id) {
+                Some(UringOperation {
+                    status: OperationStatus::Multishot(completions),
                     ..
-                } => {
-                    let res = *res;
-                    let op = op.remove();
-                    Ok((res, op.kind))
-                }
-                _ => {
-                    self.ring.wait_for(&mut context, self.id)?;
-                    match context.state.map.remove(&self.id) {
-                        Some(UringOperation {
-                            kind,
-                            status: OperationStatus::Completed(res),
-                        }) => Ok((res, kind)),
-                        _ => unreachable!(
-                            "no completed entry for {} in state after `wait_for`",
-                            self.id
-                        ),
+                }) => completions.pop_front(),
+                Some(_) => None,
+                None => return Ok(None),
+            };
+            match next {
+                Some((res, flags)) => {
+                    if flags & IORING_CQE_F_MORE == 0 {
+                        context.state.map.remove(self.id);
                     }
+                    return Ok(Some((res, flags)));
                 }
-            },
-            _ => unreachable!("no entry for {} in state", self.id),
+                None => self.ring.wait_for(&mut context, self.id)?,
+            }
         }
     }
 }
 
 impl<'a> Drop for Handle<'a> {
     fn drop(&mut self) {
-        let mut state = self.ring.state.borrow_mut();
-        if let Entry::Occupied(mut op) = state.map.entry(self.id) {
-            // Dropped before waiting on this handle; tell the Uring to ignore the result.
-            match op.get().status {
-                OperationStatus::Completed(_) => {
-                    op.remove();
-                }
-                _ => op.get_mut().status = OperationStatus::Cancelled,
+        match self.ring.context() {
+            Ok(mut context) => release(self.ring, &mut context, self.id),
+            // The entry keeps owning what the kernel may still use; release it, waiting
+            // for the completion of a borrowing operation, the next time the state is
+            // borrowed.
+            Err(_) => self.ring.dropped.borrow_mut().push(self.id),
+        }
+    }
+}
+
+/// Updates the entry of the operation `id` whose handle is dropped.
+///
+/// The kernel may still use the memory of the operation, so the entry is only removed
+/// once the operation is completed.
+pub(crate) fn release(ring: &Uring, context: &mut UringContext, id: u64) {
+    #[cfg(feature = "async")]
+    context.state.wakers.remove(&id);
+    let op = match context.state.map.get_mut(id) {
+        Some(op) => op,
+        None => return,
+    };
+    let completed = matches!(op.status, OperationStatus::Completed(..));
+    if op.kind.is_multishot() {
+        // Release the results nobody is going to take.
+        let mut armed = true;
+        if let OperationStatus::Multishot(completions) = &mut op.status {
+            for (res, flags) in completions.drain(..) {
+                op.kind.discard(res);
+                armed = flags & IORING_CQE_F_MORE != 0;
             }
         }
+        if armed {
+            // Stop the operation; `handle_cqe` discards the remaining completions
+            // and removes the entry with the last one.
+            op.status = OperationStatus::Cancelled;
+            let borrowed = op.borrowed;
+            let _ = ring.cancel(context, id);
+            while borrowed && context.state.map.contains_key(id) {
+                if ring.wait_for(context, id).is_err() {
+                    break;
+                }
+            }
+            return;
+        }
+    } else if op.borrowed && !completed {
+        // The kernel may still access the borrowed memory; wait for the completion
+        // before the borrow ends. Operations that may never complete are cancelled first.
+        if op.kind.waits_for_event() {
+            let _ = ring.cancel(context, id);
+        }
+        let _ = ring.wait_completed(context, id);
+    } else if matches!(op.kind, UringOperationKind::MsgRingReceive) {
+        // Nothing was submitted for the message; `handle_cqe` discards it if it
+        // still arrives.
+        if !completed {
+            context.state.expected_msgs -= 1;
+        }
+    } else if !completed {
+        // Dropped before waiting on this handle; tell the Uring to ignore the result.
+        // The entry keeps owning the buffer until `handle_cqe` observes the completion.
+        op.status = OperationStatus::Cancelled;
+        return;
     }
+    context.state.map.remove(id);
 }
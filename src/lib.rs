@@ -10,7 +10,7 @@
 //! let f = File::open("example.txt")?;
 //! let mut handle = uring.prepare_read(Sqe::new(ReadData {
 //!     fd: f.as_raw_fd(),
-//!     buf: UringBuf::Vec(vec![0; 1024]),
+//!     buf: UringBuf::Vec(vec![0; 1024]).into(),
 //!     offset: 0,
 //! }))?;
 //! uring.submit()?;
@@ -19,181 +19,1476 @@
 //! # }
 //! ```
 use std::{
-    cell::{RefCell, RefMut, UnsafeCell},
-    collections::{hash_map::Entry, HashMap},
-    io,
-    mem::MaybeUninit,
+    cell::{OnceCell, RefCell, RefMut, UnsafeCell},
+    collections::VecDeque,
+    fmt, io,
+    mem::{self, MaybeUninit},
+    os::unix::io::{AsRawFd, RawFd},
     ptr,
     ptr::NonNull,
+    time::{Duration, Instant},
 };
 
 use thiserror::Error;
 use uring_sys2::*;
 
 use crate::{
-    buf::UringBuf,
-    handle::{FdatasyncHandle, FsyncHandle, Handler, MadviseHandle, ReadHandle, WriteHandle},
+    buf::{BufRing, UringBuf, UringReadBuf, UringWriteBuf},
+    chain::Chain,
+    handle::{
+        BindHandle, CloseHandle, EpollCtlHandle, FadviseHandle, FdatasyncHandle, FsyncHandle,
+        FtruncateHandle, GetxattrHandle, Handler, LinkHandle, ListenHandle, MadviseHandle,
+        MkdirHandle, MsgRingHandle, MsgRingReceiveHandle, MultishotAcceptHandle,
+        MultishotRecvHandle, NopHandle, OpenatHandle, ReadHandle, RecvHandle, RecvmsgHandle,
+        RenameHandle, SendZcHandle, SendmsgHandle, SetxattrHandle, ShutdownHandle, SocketHandle,
+        SpliceHandle, SymlinkHandle, SyncFileRangeHandle, TeeHandle, UnlinkHandle, UringHandle,
+        Wait, WaitidHandle, WriteAllHandle, WriteHandle,
+    },
+    probe::{Features, OpKind, Probe},
+    result::UringResult,
+    slab::Slab,
     sqe::{
-        FdatasyncData, FsyncData, MadviseData, ReadData, Sqe, UringOperationKind, UringSqe,
-        WriteData,
+        BindData, CloseData, EpollCtlData, FadviseData, FdatasyncData, FsyncData, FtruncateData,
+        GetxattrData, LinkData, ListenData, MadviseData, MkdirData, MsgRingData,
+        MultishotAcceptData, MultishotRecvData, NopData, Offset, OpenatData, ReadData, RecvData,
+        RecvmsgData, RenameData, SendZcData, SendmsgData, SetxattrData, ShutdownData, SocketData,
+        SpliceData, Sqe, SymlinkData, SyncFileRangeData, TeeData, UnlinkData, UringOperationKind,
+        UringSqe, WaitidData, WriteData,
     },
 };
 
 pub mod buf;
+pub mod chain;
+#[cfg(feature = "async")]
+pub mod future;
 pub mod handle;
+pub mod probe;
 pub mod result;
+mod slab;
 pub mod sqe;
+pub mod sync;
 
 /// liburing interface without `async`.
 pub struct Uring {
     ring: UnsafeCell<io_uring>,
     state: RefCell<UringState>,
+    /// Ids of the handles dropped while the state was borrowed, released by the next
+    /// [`context`](Uring::context).
+    dropped: RefCell<Vec<u64>>,
+    sq_full_policy: SqFullPolicy,
+    drop_timeout: Option<Duration>,
+    /// Probed on the first preparation of an operation that needs a recent kernel.
+    supported: OnceCell<Probe>,
+}
+
+impl fmt::Debug for Uring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Uring");
+        d.field("entries", &(self.sq_space_left() + self.sq_ready()))
+            .field("sq_full_policy", &self.sq_full_policy);
+        match self.state.try_borrow() {
+            Ok(state) => d
+                .field("submitted_count", &state.submitted_count)
+                .field("expected_msgs", &state.expected_msgs)
+                .field("operations", &state.map.len()),
+            Err(_) => d.field("state", &"<borrowed>"),
+        };
+        d.finish()
+    }
 }
 
+/// What [`Uring`] does when preparing an operation while the SQ is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqFullPolicy {
+    /// Submits the prepared SQEs to make room, then retries.
+    #[default]
+    AutoSubmit,
+    /// Fails with [`Error::SqFull`] and leaves submission to the caller.
+    Error,
+}
+
+/// Builder of [`Uring`] with setup flags (`IORING_SETUP_*`).
+#[derive(Debug)]
+pub struct UringBuilder {
+    entries: usize,
+    flags: u32,
+    sq_full_policy: SqFullPolicy,
+    register_ring_fd: bool,
+    drop_timeout: Option<Duration>,
+    sq_thread_idle: u32,
+    sq_thread_cpu: u32,
+}
+
+impl UringBuilder {
+    /// Creates a new builder of a `Uring` with `entries` SQ entries.
+    pub fn new(entries: usize) -> UringBuilder {
+        UringBuilder {
+            entries,
+            flags: 0,
+            sq_full_policy: SqFullPolicy::default(),
+            register_ring_fd: false,
+            drop_timeout: Some(DEFAULT_DROP_TIMEOUT),
+            sq_thread_idle: 0,
+            sq_thread_cpu: 0,
+        }
+    }
+
+    /// Registers the ring fd on creation.
+    ///
+    /// See [`Uring::register_ring_fd`].
+    pub fn register_ring_fd(mut self) -> UringBuilder {
+        self.register_ring_fd = true;
+        self
+    }
+
+    /// Sets what to do when preparing an operation while the SQ is full.
+    ///
+    /// Defaults to [`SqFullPolicy::AutoSubmit`].
+    pub fn sq_full_policy(mut self, policy: SqFullPolicy) -> UringBuilder {
+        self.sq_full_policy = policy;
+        self
+    }
+
+    /// Sets how long dropping the `Uring` waits for the operations in flight.
+    ///
+    /// Dropping cancels the operations in flight and waits for their completions, as
+    /// the kernel may use their memory until then. An operation that ignores the
+    /// cancellation would block the drop forever; after `timeout`, the drop gives up
+    /// and leaks the memory of the remaining operations instead. `None` waits without
+    /// a bound. Defaults to 1 second. Use [`Uring::close_ring`] to observe the errors.
+    pub fn drop_timeout(mut self, timeout: Option<Duration>) -> UringBuilder {
+        self.drop_timeout = timeout;
+        self
+    }
+
+    /// Lets a kernel thread poll the SQ, so that submitting needs no system call while
+    /// the thread is awake.
+    ///
+    /// Sets `IORING_SETUP_SQPOLL`. The thread goes to sleep after `idle` without new
+    /// entries; [`Uring::submit`] then wakes it up. Kernels before 5.11 require
+    /// `CAP_SYS_ADMIN` and, without [`Features::sqpoll_nonfixed`], registered files.
+    pub fn sqpoll(mut self, idle: Duration) -> UringBuilder {
+        self.flags |= IORING_SETUP_SQPOLL;
+        self.sq_thread_idle = idle.as_millis().min(u32::MAX as u128) as u32;
+        self
+    }
+
+    /// Pins the SQ polling thread set up by [`sqpoll`](UringBuilder::sqpoll) to `cpu`.
+    ///
+    /// Sets `IORING_SETUP_SQ_AFF`.
+    pub fn sq_thread_cpu(mut self, cpu: u32) -> UringBuilder {
+        self.flags |= IORING_SETUP_SQ_AFF;
+        self.sq_thread_cpu = cpu;
+        self
+    }
+
+    /// Tells the kernel that only one thread submits requests.
+    ///
+    /// Sets `IORING_SETUP_SINGLE_ISSUER`. A `Uring` cannot be sent to another thread,
+    /// so this always holds.
+    pub fn single_issuer(mut self) -> UringBuilder {
+        self.flags |= IORING_SETUP_SINGLE_ISSUER;
+        self
+    }
+
+    /// Lets the kernel run completion work when the task enters the kernel instead of
+    /// interrupting it.
+    ///
+    /// Sets `IORING_SETUP_COOP_TASKRUN`.
+    pub fn coop_taskrun(mut self) -> UringBuilder {
+        self.flags |= IORING_SETUP_COOP_TASKRUN;
+        self
+    }
+
+    /// Defers completion work until the task waits for completions.
+    ///
+    /// Sets `IORING_SETUP_DEFER_TASKRUN` together with the required
+    /// `IORING_SETUP_SINGLE_ISSUER`. Completions are posted only while waiting on a
+    /// handle, which enters the kernel to get events. Until then, [`Uring::cq_ready`]
+    /// and [`Uring::completions`] do not see them.
+    pub fn defer_taskrun(mut self) -> UringBuilder {
+        self.flags |= IORING_SETUP_DEFER_TASKRUN | IORING_SETUP_SINGLE_ISSUER;
+        self
+    }
+
+    /// Creates the `Uring`.
+    ///
+    /// Equivalent to `io_uring_queue_init_params`.
+    pub fn build(self) -> Result<Uring> {
+        let mut ring = MaybeUninit::uninit();
+        let ring = unsafe {
+            let mut params: io_uring_params = mem::zeroed();
+            params.flags = self.flags;
+            params.sq_thread_idle = self.sq_thread_idle;
+            params.sq_thread_cpu = self.sq_thread_cpu;
+            let ret =
+                io_uring_queue_init_params(self.entries as u32, ring.as_mut_ptr(), &mut params);
+            if ret < 0 {
+                return Err(Error::InitError(
+                    io::Error::from_raw_os_error(-ret),
+                    self.entries,
+                ));
+            }
+            UnsafeCell::new(ring.assume_init())
+        };
+
+        let uring = Uring {
+            ring,
+            state: RefCell::new(UringState::new(self.entries)),
+            dropped: RefCell::new(Vec::new()),
+            sq_full_policy: self.sq_full_policy,
+            drop_timeout: self.drop_timeout,
+            supported: OnceCell::new(),
+        };
+        if self.register_ring_fd {
+            uring.register_ring_fd()?;
+        }
+        Ok(uring)
+    }
+}
+
+const DEFAULT_DROP_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Internal state.
 struct UringState {
-    id_gen: u64,
-    /// Keeps track of ongoing/completed io_uring operations.
-    map: HashMap<u64, UringOperation>,
+    /// Keeps track of ongoing/completed io_uring operations, keyed by `user_data`.
+    map: Slab<UringOperation>,
     submitted_count: usize,
+    /// Messages expected with [`Uring::expect_msg_ring`]. No SQE is submitted for them,
+    /// so they are counted apart from `submitted_count` and never waited for as a whole.
+    expected_msgs: usize,
+    /// Ids of the SQEs not consumed by the kernel yet, in the order of the SQ.
+    unsubmitted: VecDeque<u64>,
+    /// Buffers registered with `io_uring_register_buffers`, indexed by `buf_index`.
+    registered_buffers: Vec<Vec<u8>>,
+    /// The ring fd is registered with `io_uring_register_ring_fd`.
+    ring_fd_registered: bool,
+    /// Wakers of the futures waiting on operations, woken when a completion is processed.
+    #[cfg(feature = "async")]
+    wakers: std::collections::HashMap<u64, std::task::Waker>,
+}
+
+impl UringState {
+    fn new(entries: usize) -> Self {
+        UringState {
+            map: Slab::with_capacity(entries),
+            submitted_count: 0,
+            expected_msgs: 0,
+            unsubmitted: VecDeque::new(),
+            registered_buffers: Vec::new(),
+            ring_fd_registered: false,
+            #[cfg(feature = "async")]
+            wakers: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Removes the operation `id` if it is completed, returning its result and data.
+    fn take_completed(&mut self, id: u64) -> Option<(i32, u32, UringOperationKind)> {
+        match self.map.get(id)?.status {
+            OperationStatus::Completed(..) => {}
+            _ => return None,
+        }
+        match self.map.remove(id)? {
+            UringOperation {
+                status: OperationStatus::Completed(res, flags),
+                kind,
+                ..
+            } => Some((res, flags, kind)),
+            _ => None,
+        }
+    }
+}
+
+struct UringContext<'a> {
+    state: RefMut<'a, UringState>,
+}
+
+/// Errors from [`Uring`](Uring).
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("io_uring_queue_init_params({1}) failed")]
+    InitError(#[source] io::Error, usize),
+    #[error("io_uring_get_sqe failed")]
+    GetSqeError,
+    #[error("submission queue is full")]
+    SqFull,
+    #[error("invalid SQE: {0}")]
+    InvalidSqe(&'static str),
+    #[error("io_uring_submit failed")]
+    SubmitError(#[source] io::Error),
+    #[error("io_uring_wait_cqe failed")]
+    WaitCqeError(#[source] io::Error),
+    #[error("io_uring_wait_cqe failed: resource temporarily unavailable")]
+    WaitCqeAgain,
+    #[error("io_uring_register_buffers failed")]
+    RegisterBuffersError(#[source] io::Error),
+    #[error("io_uring_register_files_sparse failed")]
+    RegisterFilesError(#[source] io::Error),
+    #[error("io_uring_register_ring_fd failed")]
+    RegisterRingFdError(#[source] io::Error),
+    #[error("io_uring_register_buf_ring failed")]
+    RegisterBufRingError(#[source] io::Error),
+    #[error("io_uring_register_iowq_max_workers failed")]
+    RegisterIowqMaxWorkersError(#[source] io::Error),
+    #[error("io_uring_get_probe_ring failed")]
+    ProbeError,
+    #[error("{0:?} is not supported by the running kernel")]
+    Unsupported(OpKind),
+    #[error("internal error: no entry in the state map for id {0}")]
+    MissingOperation(u64),
+    #[error("internal error: wait_for({0}) could not find the operation with the given id")]
+    CompletionNotFound(u64),
+    #[error("internal error: invalid conversion from UringOperationKind to {expected}")]
+    UnexpectedOperationKind { expected: &'static str },
+    #[error("the Uring is already in use, e.g. by a waker called on a completion")]
+    Busy,
+    #[error("write_all failed after writing {written} bytes")]
+    WriteAllError {
+        written: usize,
+        #[source]
+        source: io::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for io::Error {
+    /// Unwraps the OS error of the failed syscall if there is one; other errors
+    /// become [`io::ErrorKind::Other`].
+    fn from(e: Error) -> io::Error {
+        match e {
+            Error::InitError(e, _)
+            | Error::SubmitError(e)
+            | Error::WaitCqeError(e)
+            | Error::RegisterBuffersError(e)
+            | Error::RegisterFilesError(e)
+            | Error::RegisterRingFdError(e)
+            | Error::RegisterBufRingError(e)
+            | Error::RegisterIowqMaxWorkersError(e)
+            | Error::WriteAllError { source: e, .. } => e,
+            Error::WaitCqeAgain => io::Error::from_raw_os_error(libc::EAGAIN),
+            e => io::Error::new(io::ErrorKind::Other, e),
+        }
+    }
 }
 
-impl UringState {
-    fn new(entries: usize) -> Self {
-        UringState {
-            id_gen: 0,
-            map: HashMap::with_capacity(entries),
-            submitted_count: 0,
+impl Uring {
+    /// Creates a new `Uring`.
+    pub fn new(entries: usize) -> Result<Self> {
+        UringBuilder::new(entries).build()
+    }
+
+    /// Returns a builder to create a `Uring` with setup flags.
+    pub fn builder(entries: usize) -> UringBuilder {
+        UringBuilder::new(entries)
+    }
+
+    /// Submits pending SQEs.
+    ///
+    /// Returns the number of submitted entries. With [`UringBuilder::sqpoll`], the
+    /// kernel thread picks up the entries by itself; like `io_uring_submit`, this
+    /// enters the kernel only to wake up the thread if it has `IORING_SQ_NEED_WAKEUP`
+    /// set.
+    pub fn submit(&self) -> Result<usize> {
+        self.submit_with_context(&mut self.context()?)
+    }
+
+    /// Submits pending SQEs, also reporting the entries left in the SQ.
+    ///
+    /// The kernel may consume only part of the SQ, e.g. when it runs out of memory
+    /// for the requests; submit again, or use [`submit_all`](Uring::submit_all), until
+    /// [`SubmitStatus::is_complete`] returns true.
+    pub fn submit_with_status(&self) -> Result<SubmitStatus> {
+        let submitted = self.submit_with_context(&mut self.context()?)?;
+        Ok(SubmitStatus {
+            submitted,
+            pending: self.sq_ready(),
+        })
+    }
+
+    /// Submits pending SQEs and waits for the next completion of an operation.
+    ///
+    /// Returns the id of the operation (see [`ReadHandle::id`]) and its result, or
+    /// `None` if no operation is in flight. Like [`completions`](Uring::completions),
+    /// the completion is recorded so that the handle returns it without blocking, and
+    /// completions of dropped handles are skipped.
+    pub fn submit_and_wait_one(&self) -> Result<Option<(OperationId, i32)>> {
+        let mut context = self.context()?;
+        self.submit_with_context(&mut context)?;
+        while let Some(id) = self.wait_single_cqe(&mut context)? {
+            let res = match context.state.map.get(id).map(|op| &op.status) {
+                Some(OperationStatus::Completed(res, _))
+                | Some(OperationStatus::Notifying(res, _)) => *res,
+                Some(OperationStatus::Multishot(completions)) => match completions.back() {
+                    Some((res, _)) => *res,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            return Ok(Some((OperationId(id), res)));
+        }
+        Ok(None)
+    }
+
+    /// Submits pending SQEs until the SQ is empty.
+    ///
+    /// Returns the total number of submitted entries. Unlike [`submit`](Uring::submit),
+    /// this keeps submitting if the kernel consumes only part of the SQ.
+    pub fn submit_all(&self) -> Result<usize> {
+        let mut context = self.context()?;
+        let mut total = 0;
+        while self.sq_ready() > 0 {
+            match self.submit_with_context(&mut context)? {
+                // Nothing was consumed, so another attempt would not make progress.
+                0 => break,
+                submitted => total += submitted,
+            }
+        }
+        Ok(total)
+    }
+
+    /// Cancels the operations in flight, waits for all of their completions and
+    /// destroys the ring.
+    ///
+    /// Unlike dropping the `Uring`, this waits without a bound (see
+    /// [`UringBuilder::drop_timeout`]) and reports the errors. If it fails, the rest is
+    /// left to the drop.
+    pub fn close_ring(self) -> Result<()> {
+        let mut context = self.context()?;
+        self.cancel_inflight(&mut context)?;
+        while self.wait_single_cqe(&mut context)?.is_some() {}
+        Ok(())
+    }
+
+    /// Submits pending SQEs and requests the kernel to cancel every operation that has
+    /// not completed yet.
+    ///
+    /// Returns the number of operations targeted. Cancelled operations complete with
+    /// `ECANCELED`, unless they completed before the cancellation took effect. Use it
+    /// for a quick teardown instead of waiting for slow operations.
+    pub fn cancel_all(&self) -> Result<usize> {
+        self.cancel_inflight(&mut self.context()?)
+    }
+
+    /// Submits pending SQEs and waits until every ongoing operation completes.
+    ///
+    /// Armed multishot operations are cancelled. Afterwards, the results of all
+    /// operations are discarded, and waiting on their handles fails with
+    /// [`Error::MissingOperation`].
+    pub fn drain(&self) -> Result<()> {
+        let mut context = self.context()?;
+        self.submit_with_context(&mut context)?;
+
+        let armed = context
+            .state
+            .map
+            .iter()
+            .filter(|(_, op)| op.is_armed_multishot())
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        for id in armed {
+            if let Some(op) = context.state.map.get_mut(id) {
+                if let OperationStatus::Multishot(completions) = &op.status {
+                    for (res, _) in completions {
+                        op.kind.discard(*res);
+                    }
+                }
+                op.status = OperationStatus::Cancelled;
+            }
+            self.cancel(&mut context, id)?;
+        }
+
+        while self.wait_single_cqe(&mut context)?.is_some() {}
+
+        for (_, op) in context.state.map.drain() {
+            match op.status {
+                OperationStatus::Completed(res, _) => op.kind.discard(res),
+                OperationStatus::Multishot(completions) => {
+                    for (res, _) in completions {
+                        op.kind.discard(res);
+                    }
+                }
+                _ => {}
+            }
+        }
+        context.state.expected_msgs = 0;
+        Ok(())
+    }
+
+    /// Prepares for asynchronous `read(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_read`.
+    ///
+    /// If the buffer borrows a slice, the returned handle holds the borrow until the
+    /// operation completes. At end of file, the result is 0; see
+    /// [`ReadResult::is_eof`](crate::result::ReadResult::is_eof).
+    pub fn prepare_read<'a>(&'a self, entry: Sqe<ReadData<'a>>) -> Result<ReadHandle<'a>> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `read(2)` of every entry and returns the handles in
+    /// order.
+    ///
+    /// Fails without preparing anything if an entry is invalid. When the SQ fills up
+    /// in the middle of the batch, the queued SQEs are handled according to the
+    /// [`SqFullPolicy`], so a batch larger than the SQ needs
+    /// [`SqFullPolicy::AutoSubmit`].
+    pub fn prepare_read_batch<'a>(
+        &'a self,
+        entries: Vec<Sqe<ReadData<'a>>>,
+    ) -> Result<Vec<ReadHandle<'a>>> {
+        for entry in &entries {
+            entry.validate()?;
+        }
+        let mut context = self.context()?;
+        entries
+            .into_iter()
+            .map(|entry| self.prepare(&mut context, entry))
+            .collect()
+    }
+
+    /// Submits pending SQEs and waits for all `handles`, returning their results in
+    /// the same order.
+    ///
+    /// If waiting on a handle fails, the remaining handles are still waited for so
+    /// that they release what they own, and the first error is returned.
+    pub fn wait_all<H: Wait>(&self, handles: Vec<H>) -> Result<Vec<H::Output>> {
+        if self.sq_ready() > 0 {
+            self.submit_all()?;
+        }
+        let mut results = Vec::with_capacity(handles.len());
+        let mut error = None;
+        for handle in handles {
+            match handle.wait() {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(results),
+        }
+    }
+
+    /// Submits pending SQEs and waits for handles of any kind, returning their results
+    /// in the same order.
+    ///
+    /// Shortcut for [`wait_all`](Uring::wait_all) with [`UringHandle`]s, e.g. built
+    /// with `handle.into()`.
+    pub fn wait_handles(&self, handles: Vec<UringHandle>) -> Result<Vec<UringResult>> {
+        self.wait_all(handles)
+    }
+
+    /// Starts building a linked sequence of operations.
+    ///
+    /// ```rust
+    /// # use std::os::unix::io::AsRawFd;
+    /// use aluring::{buf::UringBuf, sqe::Sqe, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let uring = Uring::new(8)?;
+    /// let f = tempfile::tempfile()?;
+    /// let handles = uring
+    ///     .chain()
+    ///     .push(Sqe::write(f.as_raw_fd(), UringBuf::Vec(b"hello".to_vec()), 0))
+    ///     .push(Sqe::fdatasync(f.as_raw_fd()))
+    ///     .prepare()?;
+    /// uring.submit()?;
+    /// for handle in handles {
+    ///     handle.wait()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chain(&self) -> Chain {
+        Chain::new(self)
+    }
+
+    /// Prepares for asynchronous `read(2)` that is cancelled if it does not complete
+    /// within `timeout`.
+    ///
+    /// Equivalent to `io_uring_prep_read` linked to `io_uring_prep_link_timeout`. On
+    /// expiry, the read completes with `ECANCELED`.
+    ///
+    /// Like a [`Chain`], both SQEs are reserved before either is prepared, so that the
+    /// read is never submitted without its timeout. Fails with [`Error::SqFull`] if the
+    /// SQ still lacks two free entries after handling the queued SQEs according to the
+    /// [`SqFullPolicy`].
+    pub fn prepare_read_with_timeout<'a>(
+        &'a self,
+        entry: Sqe<ReadData<'a>>,
+        timeout: Duration,
+    ) -> Result<ReadHandle<'a>> {
+        if self.sq_space_left() + self.sq_ready() < 2 {
+            return Err(Error::InvalidSqe("read with timeout longer than the SQ"));
+        }
+        let mut context = self.context()?;
+        // The timeout must follow the read in the same submission.
+        if self.sq_space_left() < 2 {
+            self.make_room(&mut context)?;
+            // The kernel may consume only part of the SQ.
+            if self.sq_space_left() < 2 {
+                return Err(Error::SqFull);
+            }
+        }
+        let handle = self.prepare(&mut context, entry.link())?;
+        self.link_timeout(&mut context, timeout)?;
+        Ok(handle)
+    }
+
+    /// Prepares for asynchronous `write(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_write`.
+    ///
+    /// If the buffer borrows a slice, the returned handle holds the borrow until the
+    /// operation completes.
+    pub fn prepare_write<'a>(&'a self, entry: Sqe<WriteData<'a>>) -> Result<WriteHandle<'a>> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `write(2)` whose result nobody waits for.
+    ///
+    /// The ring owns `buf` until the operation completes and then drops it, along with
+    /// the result. Use [`drain`](Uring::drain) to wait until detached writes are done.
+    pub fn write_detached(
+        &self,
+        fd: RawFd,
+        buf: UringBuf,
+        offset: impl Into<Offset>,
+    ) -> Result<()> {
+        // Dropping the handle of an operation that owns its buffer leaves the entry
+        // to `handle_cqe`, which releases it on completion.
+        self.prepare_write(Sqe::write(fd, buf, offset)).map(drop)
+    }
+
+    /// Prepares for asynchronous `write(2)` of the whole buffer.
+    ///
+    /// Like [`std::io::Write::write_all`], the returned handle reissues the unwritten
+    /// tail of a short write at the advanced offset until everything is written or a
+    /// write fails. The retries are not linked to other operations.
+    ///
+    /// The retries of a write from [`UringWriteBuf::from_slice`] borrow the same slice,
+    /// so the returned handle takes over the safety contract of the handle of the
+    /// first write.
+    pub fn prepare_write_all<'a>(
+        &'a self,
+        entry: Sqe<WriteData<'a>>,
+    ) -> Result<WriteAllHandle<'a>> {
+        if entry.data.buf.len() > i32::MAX as usize {
+            return Err(Error::InvalidSqe("buffer too large for write_all"));
+        }
+        let (fd, offset) = (entry.data.fd, entry.data.offset);
+        let slice = entry.data.buf.as_slice();
+        let fd_borrowed = entry.fd_borrowed;
+        let handle = self.prepare_write(entry)?;
+        Ok(WriteAllHandle::new(
+            handle,
+            self,
+            fd,
+            offset,
+            slice,
+            fd_borrowed,
+        ))
+    }
+
+    pub fn prepare_fsync(&self, entry: Sqe<FsyncData>) -> Result<FsyncHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    pub fn prepare_fdatasync(&self, entry: Sqe<FdatasyncData>) -> Result<FdatasyncHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    pub fn prepare_madvise(&self, entry: Sqe<MadviseData>) -> Result<MadviseHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `posix_fadvise(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_fadvise`.
+    pub fn prepare_fadvise(&self, entry: Sqe<FadviseData>) -> Result<FadviseHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `ftruncate(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_ftruncate`. Returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_ftruncate(&self, entry: Sqe<FtruncateData>) -> Result<FtruncateHandle> {
+        self.check_supported(OpKind::Ftruncate)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous multishot `accept4(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_multishot_accept`. The returned handle yields
+    /// an accepted connection per [`wait`](MultishotAcceptHandle::wait).
+    pub fn prepare_multishot_accept(
+        &self,
+        entry: Sqe<MultishotAcceptData>,
+    ) -> Result<MultishotAcceptHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `shutdown(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_shutdown`.
+    pub fn prepare_shutdown(&self, entry: Sqe<ShutdownData>) -> Result<ShutdownHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `socket(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_socket`. Requires Linux 5.19; returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_socket(&self, entry: Sqe<SocketData>) -> Result<SocketHandle> {
+        self.check_supported(OpKind::Socket)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `close(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_close`.
+    pub fn prepare_close(&self, entry: Sqe<CloseData>) -> Result<CloseHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `recv(2)` with buffer selection.
+    ///
+    /// Equivalent to `io_uring_prep_recv` with `IOSQE_BUFFER_SELECT`. Dropping the
+    /// handle before completion cancels the operation and waits for it.
+    pub fn prepare_recv<'a>(&'a self, entry: Sqe<RecvData<'a>>) -> Result<RecvHandle<'a>> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous multishot `recv(2)` with buffer selection.
+    ///
+    /// Equivalent to `io_uring_prep_recv_multishot` with `IOSQE_BUFFER_SELECT`. The
+    /// returned handle yields a [`RecvResult`](result::RecvResult) per received chunk
+    /// of data. Buffers of results that are not taken are not returned to `buf_ring`.
+    pub fn prepare_multishot_recv<'a>(
+        &'a self,
+        entry: Sqe<MultishotRecvData<'a>>,
+    ) -> Result<MultishotRecvHandle<'a>> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `sendmsg(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_sendmsg`.
+    pub fn prepare_sendmsg(&self, entry: Sqe<SendmsgData>) -> Result<SendmsgHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `recvmsg(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_recvmsg`.
+    pub fn prepare_recvmsg(&self, entry: Sqe<RecvmsgData>) -> Result<RecvmsgHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `renameat2(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_renameat`.
+    pub fn prepare_rename(&self, entry: Sqe<RenameData>) -> Result<RenameHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `unlinkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_unlinkat`.
+    pub fn prepare_unlink(&self, entry: Sqe<UnlinkData>) -> Result<UnlinkHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `mkdirat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_mkdirat`.
+    pub fn prepare_mkdir(&self, entry: Sqe<MkdirData>) -> Result<MkdirHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `symlinkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_symlinkat`.
+    pub fn prepare_symlink(&self, entry: Sqe<SymlinkData>) -> Result<SymlinkHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `linkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_linkat`.
+    pub fn prepare_hard_link(&self, entry: Sqe<LinkData>) -> Result<LinkHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous no-op.
+    ///
+    /// Equivalent to `io_uring_prep_nop`.
+    pub fn prepare_nop(&self, entry: Sqe<NopData>) -> Result<NopHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `splice(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_splice`.
+    pub fn prepare_splice(&self, entry: Sqe<SpliceData>) -> Result<SpliceHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `tee(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_tee`.
+    pub fn prepare_tee(&self, entry: Sqe<TeeData>) -> Result<TeeHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for posting a completion on another ring.
+    ///
+    /// Equivalent to `io_uring_prep_msg_ring`. Use [`Uring::ring_fd`] of the target
+    /// ring and the id of a handle returned by its
+    /// [`expect_msg_ring`](Uring::expect_msg_ring) as `user_data`.
+    pub fn prepare_msg_ring(&self, entry: Sqe<MsgRingData>) -> Result<MsgRingHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `epoll_ctl(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_epoll_ctl`.
+    pub fn prepare_epoll_ctl(&self, entry: Sqe<EpollCtlData>) -> Result<EpollCtlHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `waitid(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_waitid`. Requires Linux 6.7; returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_waitid(&self, entry: Sqe<WaitidData>) -> Result<WaitidHandle> {
+        self.check_supported(OpKind::Waitid)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `setxattr(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_setxattr`.
+    pub fn prepare_setxattr(&self, entry: Sqe<SetxattrData>) -> Result<SetxattrHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `getxattr(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_getxattr`. The result is the length of the value.
+    pub fn prepare_getxattr(&self, entry: Sqe<GetxattrData>) -> Result<GetxattrHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `sync_file_range(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_sync_file_range`. See
+    /// [`SyncFileRangeResult`](crate::result::SyncFileRangeResult) for its weaker
+    /// guarantees compared to [`prepare_fdatasync`](Uring::prepare_fdatasync).
+    pub fn prepare_sync_file_range(
+        &self,
+        entry: Sqe<SyncFileRangeData>,
+    ) -> Result<SyncFileRangeHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous zero-copy `send(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_send_zc`. The operation completes twice: first
+    /// with the result, then with a notification once the kernel releases the buffer.
+    /// The handle yields the result after the notification, so the buffer is never
+    /// reclaimed while the kernel may still read it.
+    ///
+    /// Zero-copy is only supported by some sockets, e.g. TCP and UDP; others fail
+    /// with `EOPNOTSUPP`. Requires Linux 6.0; returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_send_zc(&self, entry: Sqe<SendZcData>) -> Result<SendZcHandle> {
+        self.check_supported(OpKind::SendZc)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `openat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_openat`. A direct open, created by
+    /// [`Sqe::openat_direct`], needs a file table registered with
+    /// [`register_files_sparse`](Uring::register_files_sparse) and results in the
+    /// index of the allocated slot.
+    pub fn prepare_openat(&self, entry: Sqe<OpenatData>) -> Result<OpenatHandle> {
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `bind(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_bind`. Requires Linux 6.11; returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_bind(&self, entry: Sqe<BindData>) -> Result<BindHandle> {
+        self.check_supported(OpKind::Bind)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Prepares for asynchronous `listen(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_listen`. Requires Linux 6.11; returns
+    /// [`Error::Unsupported`](Error::Unsupported) if the running kernel does not support it.
+    pub fn prepare_listen(&self, entry: Sqe<ListenData>) -> Result<ListenHandle> {
+        self.check_supported(OpKind::Listen)?;
+        self.prepare(&mut self.context()?, entry)
+    }
+
+    /// Expects a message from another ring.
+    ///
+    /// The returned handle completes when another ring sends a message with its
+    /// [`id`](MsgRingReceiveHandle::id), converted to `u64`, as `user_data` to this
+    /// ring. No SQE is submitted for the message, so it is not in flight:
+    /// [`drain`](Uring::drain), [`cancel_all`](Uring::cancel_all) and dropping the
+    /// `Uring` do not wait for it. Dropping the handle stops expecting the message, and
+    /// the message is discarded if it arrives later.
+    pub fn expect_msg_ring(&self) -> Result<MsgRingReceiveHandle> {
+        let mut context = self.context()?;
+        let id = context.state.map.insert(UringOperation {
+            status: OperationStatus::Ongoing,
+            kind: UringOperationKind::MsgRingReceive,
+            borrowed: false,
+            tag: None,
+        });
+        context.state.expected_msgs += 1;
+        Ok(MsgRingReceiveHandle::new(id, self))
+    }
+
+    /// Prepares for asynchronous `read(2)` without building the [`Sqe`].
+    ///
+    /// Shortcut for [`prepare_read`](Uring::prepare_read) with [`Sqe::read`].
+    ///
+    /// ```rust
+    /// # use std::{io::Write, os::unix::io::AsRawFd};
+    /// use aluring::{buf::UringBuf, result::{BufIoResult, IoResult}, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let mut f = tempfile::tempfile()?;
+    /// # f.write_all(b"hello")?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0)?;
+    /// uring.submit()?;
+    /// let (len, buf) = handle.wait()?.into_parts();
+    /// assert_eq!(&buf.as_slice()[..len?], b"hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read<'a>(
+        &'a self,
+        fd: RawFd,
+        buf: impl Into<UringReadBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Result<ReadHandle<'a>> {
+        self.prepare_read(Sqe::read(fd, buf, offset))
+    }
+
+    /// Reads `fd` from `offset` until the end of the file.
+    ///
+    /// Keeps a few reads of consecutive chunks in flight, and submits and waits for
+    /// them by itself. A short read restarts the reads after the received data, so only
+    /// a read returning 0 ends the loop. `offset` must be explicit, as the chunks are
+    /// read at their own offsets. Errors of the reads are returned as they are, along
+    /// with the errors of the ring converted into [`io::Error`].
+    pub fn read_to_end(&self, fd: RawFd, offset: u64) -> io::Result<Vec<u8>> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+        const DEPTH: usize = 4;
+
+        let mut data = Vec::new();
+        let mut handles = VecDeque::with_capacity(DEPTH);
+        let mut next = offset;
+        loop {
+            while handles.len() < DEPTH {
+                let entry = Sqe::read(fd, UringBuf::uninit(CHUNK_SIZE), next);
+                handles.push_back(self.prepare_read(entry)?);
+                next += CHUNK_SIZE as u64;
+            }
+            self.submit()?;
+
+            let (res, buf) = handles.pop_front().unwrap().wait()?.into_parts();
+            match res? {
+                0 => return Ok(data),
+                n if n < CHUNK_SIZE => {
+                    // The chunks in flight no longer follow the data.
+                    handles.clear();
+                    data.extend_from_slice(buf.as_slice());
+                    next = offset + data.len() as u64;
+                }
+                _ => data.extend_from_slice(buf.as_slice()),
+            }
+        }
+    }
+
+    /// Prepares for asynchronous `write(2)` without building the [`Sqe`].
+    ///
+    /// Shortcut for [`prepare_write`](Uring::prepare_write) with [`Sqe::write`].
+    ///
+    /// ```rust
+    /// # use std::os::unix::io::AsRawFd;
+    /// use aluring::{buf::UringBuf, result::IoResult, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let f = tempfile::tempfile()?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.write(f.as_raw_fd(), UringBuf::Vec(b"hello".to_vec()), 0)?;
+    /// uring.submit()?;
+    /// assert_eq!(handle.wait()?.as_io_result()?, 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write<'a>(
+        &'a self,
+        fd: RawFd,
+        buf: impl Into<UringWriteBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Result<WriteHandle<'a>> {
+        self.prepare_write(Sqe::write(fd, buf, offset))
+    }
+
+    /// Prepares for asynchronous `fsync(2)` without building the [`Sqe`].
+    ///
+    /// Shortcut for [`prepare_fsync`](Uring::prepare_fsync) with [`Sqe::fsync`].
+    ///
+    /// ```rust
+    /// # use std::os::unix::io::AsRawFd;
+    /// use aluring::{result::IoResult, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let f = tempfile::tempfile()?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.fsync(f.as_raw_fd())?;
+    /// uring.submit()?;
+    /// handle.wait()?.as_io_result()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fsync(&self, fd: RawFd) -> Result<FsyncHandle> {
+        self.prepare_fsync(Sqe::fsync(fd))
+    }
+
+    /// Prepares for asynchronous `fdatasync(2)` without building the [`Sqe`].
+    ///
+    /// Shortcut for [`prepare_fdatasync`](Uring::prepare_fdatasync) with
+    /// [`Sqe::fdatasync`].
+    ///
+    /// ```rust
+    /// # use std::os::unix::io::AsRawFd;
+    /// use aluring::{result::IoResult, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let f = tempfile::tempfile()?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.fdatasync(f.as_raw_fd())?;
+    /// uring.submit()?;
+    /// handle.wait()?.as_io_result()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fdatasync(&self, fd: RawFd) -> Result<FdatasyncHandle> {
+        self.prepare_fdatasync(Sqe::fdatasync(fd))
+    }
+
+    /// Prepares for asynchronous `close(2)` without building the [`Sqe`].
+    ///
+    /// Shortcut for [`prepare_close`](Uring::prepare_close) with [`Sqe::close`].
+    ///
+    /// ```rust
+    /// # use std::os::unix::io::IntoRawFd;
+    /// use aluring::{result::IoResult, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let f = tempfile::tempfile()?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.close(f.into_raw_fd())?;
+    /// uring.submit()?;
+    /// handle.wait()?.as_io_result()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn close(&self, fd: RawFd) -> Result<CloseHandle> {
+        self.prepare_close(Sqe::close(fd))
+    }
+
+    /// Registers a ring of `entries` provided buffers of `buf_len` bytes each as the
+    /// buffer group `bgid`.
+    ///
+    /// Equivalent to `io_uring_register_buf_ring`. `entries` must be a nonzero power of
+    /// two no greater than 32768; otherwise this fails with
+    /// [`Error::InvalidSqe`](Error::InvalidSqe) before anything is allocated.
+    pub fn register_buf_ring(&self, entries: u16, buf_len: usize, bgid: u16) -> Result<BufRing> {
+        BufRing::new(self, entries, buf_len, bgid)
+    }
+
+    /// Registers `bufs` with the kernel as fixed buffers, indexed in the given order.
+    ///
+    /// Equivalent to `io_uring_register_buffers`. The ring keeps the buffers until it
+    /// is dropped. Only one set of buffers can be registered at a time.
+    pub fn register_buffers(&self, bufs: Vec<Vec<u8>>) -> Result<()> {
+        let mut context = self.context()?;
+        let mut iovecs = bufs
+            .iter()
+            .map(|buf| libc::iovec {
+                iov_base: buf.as_ptr() as *mut _,
+                iov_len: buf.len(),
+            })
+            .collect::<Vec<_>>();
+        let ret = unsafe {
+            io_uring_register_buffers(
+                self.ring.get(),
+                iovecs.as_mut_ptr() as *const _,
+                iovecs.len() as u32,
+            )
+        };
+        if ret < 0 {
+            return Err(Error::RegisterBuffersError(io::Error::from_raw_os_error(
+                -ret,
+            )));
+        }
+        context.state.registered_buffers = bufs;
+        Ok(())
+    }
+
+    /// Returns the number of buffers registered with
+    /// [`register_buffers`](Uring::register_buffers).
+    pub fn registered_buffer_count(&self) -> Result<usize> {
+        Ok(self.context()?.state.registered_buffers.len())
+    }
+
+    /// Returns `true` if `index` refers to a registered buffer.
+    pub fn is_registered_buffer(&self, index: u16) -> Result<bool> {
+        Ok(usize::from(index) < self.registered_buffer_count()?)
+    }
+
+    /// Registers a file table of `nr` empty slots, e.g. for direct opens.
+    ///
+    /// Equivalent to `io_uring_register_files_sparse`. Operations refer to a slot by
+    /// its index with [`Sqe::fixed_file`].
+    pub fn register_files_sparse(&self, nr: u32) -> Result<()> {
+        let ret = unsafe { io_uring_register_files_sparse(self.ring.get(), nr) };
+        if ret < 0 {
+            return Err(Error::RegisterFilesError(io::Error::from_raw_os_error(
+                -ret,
+            )));
+        }
+        Ok(())
+    }
+
+    /// Limits the number of io-wq worker threads the kernel starts for operations that
+    /// block, e.g. buffered reads.
+    ///
+    /// Equivalent to `io_uring_register_iowq_max_workers`. `bounded` limits the workers
+    /// for regular files and block devices, `unbounded` the ones for operations that may
+    /// wait indefinitely, e.g. on sockets; 0 keeps the current limit. The limits apply
+    /// per NUMA node to the io-wq of the calling thread. Requires Linux 5.15.
+    pub fn set_iowq_max_workers(&self, bounded: u32, unbounded: u32) -> Result<()> {
+        self.register_iowq_max_workers([bounded, unbounded])
+            .map(drop)
+    }
+
+    /// Returns the limits on the io-wq workers as `(bounded, unbounded)`.
+    ///
+    /// See [`set_iowq_max_workers`](Uring::set_iowq_max_workers).
+    pub fn iowq_max_workers(&self) -> Result<(u32, u32)> {
+        let [bounded, unbounded] = self.register_iowq_max_workers([0, 0])?;
+        Ok((bounded, unbounded))
+    }
+
+    /// Sets the limits that are not 0 and returns the previous ones.
+    fn register_iowq_max_workers(&self, mut values: [u32; 2]) -> Result<[u32; 2]> {
+        let ret =
+            unsafe { io_uring_register_iowq_max_workers(self.ring.get(), values.as_mut_ptr()) };
+        if ret < 0 {
+            return Err(Error::RegisterIowqMaxWorkersError(
+                io::Error::from_raw_os_error(-ret),
+            ));
         }
+        Ok(values)
     }
-}
-
-struct UringContext<'a> {
-    state: RefMut<'a, UringState>,
-}
 
-/// Errors from [`Uring`](Uring).
-#[derive(Debug, Error)]
-pub enum Error {
-    #[error("io_uring_queue_init({1}, 0) failed")]
-    InitError(#[source] io::Error, usize),
-    #[error("io_uring_get_sqe failed")]
-    GetSqeError,
-    #[error("io_uring_submit failed")]
-    SubmitError(#[source] io::Error),
-    #[error("io_uring_wait_cqe failed")]
-    WaitCqeError(#[source] io::Error),
-    #[error("internal error: {0}")]
-    InternalError(String), // FIXME: add internal errors instead of raw strings.
-}
+    /// Registers the ring fd with the kernel so that entering the kernel, e.g. on
+    /// [`submit`](Uring::submit), skips looking up the fd.
+    ///
+    /// Equivalent to `io_uring_register_ring_fd`. The registration belongs to the
+    /// registering thread, so the `Uring` must be used from that thread only; as a
+    /// `Uring` cannot be sent to another thread, this always holds, except for the ring
+    /// of a [`SyncUring`](sync::SyncUring). Dropping the `Uring` unregisters the fd.
+    pub fn register_ring_fd(&self) -> Result<()> {
+        let mut context = self.context()?;
+        if context.state.ring_fd_registered {
+            return Ok(());
+        }
+        let ret = unsafe { io_uring_register_ring_fd(self.ring.get()) };
+        if ret < 0 {
+            return Err(Error::RegisterRingFdError(io::Error::from_raw_os_error(
+                -ret,
+            )));
+        }
+        context.state.ring_fd_registered = true;
+        Ok(())
+    }
 
-pub type Result<T> = std::result::Result<T, Error>;
+    /// Returns the fd of the ring, e.g. to target it with
+    /// [`prepare_msg_ring`](Uring::prepare_msg_ring).
+    ///
+    /// Same as [`as_raw_fd`](AsRawFd::as_raw_fd); the fd must not be closed.
+    pub fn ring_fd(&self) -> RawFd {
+        self.as_raw_fd()
+    }
 
-impl Uring {
-    /// Creates a new `Uring`.
-    pub fn new(entries: usize) -> Result<Self> {
-        let mut ring = MaybeUninit::uninit();
-        let ring = unsafe {
-            let ret = io_uring_queue_init(entries as u32, ring.as_mut_ptr(), 0);
-            if ret < 0 {
-                return Err(Error::InitError(
-                    io::Error::from_raw_os_error(-ret),
-                    entries,
-                ));
-            }
-            UnsafeCell::new(ring.assume_init())
-        };
+    /// Returns the number of operations whose completion has not been observed yet.
+    ///
+    /// Counts both prepared and submitted operations, including those whose handles
+    /// were dropped and armed multishot operations. Fails with [`Error::Busy`] if the
+    /// state is borrowed.
+    pub fn inflight(&self) -> Result<usize> {
+        Ok(self
+            .context()?
+            .state
+            .map
+            .values()
+            .filter(|op| {
+                !matches!(op.kind, UringOperationKind::MsgRingReceive)
+                    && matches!(
+                        op.status,
+                        OperationStatus::Ongoing
+                            | OperationStatus::Notifying(..)
+                            | OperationStatus::Cancelled
+                    )
+                    || op.is_armed_multishot()
+            })
+            .count())
+    }
 
-        Ok(Uring {
-            ring,
-            state: RefCell::new(UringState::new(entries)),
-        })
+    /// Returns the number of free entries in the SQ.
+    ///
+    /// Equivalent to `io_uring_sq_space_left`. Use it to decide when to
+    /// [`submit`](Uring::submit) a batch; when the SQ is full, preparing another
+    /// operation submits the queued entries implicitly.
+    pub fn sq_space_left(&self) -> usize {
+        unsafe { io_uring_sq_space_left(self.ring.get()) as usize }
     }
 
-    /// Submits pending SQEs.
+    /// Returns the number of prepared entries in the SQ that are not submitted yet.
     ///
-    /// Returns the number of submitted entries.
-    pub fn submit(&self) -> Result<usize> {
-        self.submit_with_context(&mut self.context())
+    /// Equivalent to `io_uring_sq_ready`.
+    pub fn sq_ready(&self) -> usize {
+        unsafe { io_uring_sq_ready(self.ring.get()) as usize }
     }
 
-    /// Prepares for asynchronous `read(2)`.
+    /// Returns the number of completions ready in the CQ without consuming them.
     ///
-    /// Equivalent to `io_uring_prep_read`.
-    pub fn prepare_read(&self, entry: Sqe<ReadData>) -> Result<ReadHandle> {
-        self.prepare(&mut self.context(), entry)
+    /// Equivalent to `io_uring_cq_ready`.
+    pub fn cq_ready(&self) -> usize {
+        unsafe { io_uring_cq_ready(self.ring.get()) as usize }
     }
 
-    /// Prepares for asynchronous `write(2)`.
+    /// Returns an iterator over the completions ready in the CQ.
     ///
-    /// Equivalent to `io_uring_prep_write`.
-    pub fn prepare_write(&self, entry: Sqe<WriteData>) -> Result<WriteHandle> {
-        self.prepare(&mut self.context(), entry)
+    /// Each item is the id of the operation (see [`ReadHandle::id`]) and its result.
+    /// The completions are recorded so that the handles return them without blocking.
+    /// The iterator never waits; it ends when the CQ is empty.
+    pub fn completions(&self) -> CompletionIter {
+        CompletionIter { ring: self }
     }
 
-    pub fn prepare_fsync(&self, entry: Sqe<FsyncData>) -> Result<FsyncHandle> {
-        self.prepare(&mut self.context(), entry)
+    /// Returns the operations supported by the running kernel.
+    ///
+    /// Equivalent to `io_uring_get_probe_ring`.
+    pub fn probe(&self) -> Result<Probe> {
+        let probe = unsafe { io_uring_get_probe_ring(self.ring.get()) };
+        NonNull::new(probe).map(Probe::new).ok_or(Error::ProbeError)
     }
 
-    pub fn prepare_fdatasync(&self, entry: Sqe<FdatasyncData>) -> Result<FdatasyncHandle> {
-        self.prepare(&mut self.context(), entry)
+    /// Returns the features of `io_uring` supported by the running kernel.
+    ///
+    /// Reported by the kernel in `io_uring_params.features` when the ring was created.
+    pub fn features(&self) -> Features {
+        Features::new(unsafe { (*self.ring.get()).features })
     }
 
-    pub fn prepare_madvise(&self, entry: Sqe<MadviseData>) -> Result<MadviseHandle> {
-        self.prepare(&mut self.context(), entry)
+    /// Fails with [`Error::Unsupported`] if the running kernel does not support `op`.
+    ///
+    /// The kernel is probed only once per ring.
+    fn check_supported(&self, op: OpKind) -> Result<()> {
+        let probe = match self.supported.get() {
+            Some(probe) => probe,
+            None => {
+                let probe = self.probe()?;
+                self.supported.get_or_init(|| probe)
+            }
+        };
+        if probe.supports(op) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(op))
+        }
     }
 
-    fn context(&self) -> UringContext {
-        UringContext {
-            state: self.state.borrow_mut(),
+    /// Borrows the state of the ring, failing with [`Error::Busy`] if it is already
+    /// borrowed, e.g. by a waker called while a completion is processed.
+    ///
+    /// The handles dropped while the state was borrowed are released first.
+    fn context(&self) -> Result<UringContext> {
+        let mut context = UringContext {
+            state: self.state.try_borrow_mut().map_err(|_| Error::Busy)?,
+        };
+        let dropped = mem::take(&mut *self.dropped.borrow_mut());
+        for id in dropped {
+            handle::release(self, &mut context, id);
         }
+        Ok(context)
     }
 
     fn wait_single_cqe(&self, context: &mut UringContext) -> Result<Option<u64>> {
         if context.state.submitted_count == 0 {
             return Ok(None);
         }
+        self.wait_cqe(context).map(Some)
+    }
+
+    /// Waits for the next completion, even if no submitted operation is in flight, e.g.
+    /// for an expected message.
+    fn wait_cqe(&self, context: &mut UringContext) -> Result<u64> {
+        let mut cqe = ptr::null_mut();
+        loop {
+            let ret = unsafe { io_uring_wait_cqe(self.ring.get(), &mut cqe) };
+            match -ret {
+                0 => return unsafe { self.handle_cqe(context, NonNull::new_unchecked(cqe)) },
+                // Interrupted by a signal handler before any completion arrived.
+                libc::EINTR => continue,
+                libc::EAGAIN => return Err(Error::WaitCqeAgain),
+                errno => return Err(Error::WaitCqeError(io::Error::from_raw_os_error(errno))),
+            }
+        }
+    }
+
+    /// Like `wait_single_cqe`, but returns `None` if no completion arrives within
+    /// `timeout`.
+    fn wait_single_cqe_timeout(
+        &self,
+        context: &mut UringContext,
+        timeout: Duration,
+    ) -> Result<Option<u64>> {
+        if context.state.submitted_count == 0 {
+            return Ok(None);
+        }
 
+        let mut ts = __kernel_timespec {
+            tv_sec: timeout.as_secs() as _,
+            tv_nsec: timeout.subsec_nanos() as _,
+        };
+        let mut cqe = ptr::null_mut();
+        loop {
+            let ret = unsafe { io_uring_wait_cqe_timeout(self.ring.get(), &mut cqe, &mut ts) };
+            match -ret {
+                0 => {
+                    return unsafe {
+                        self.handle_cqe(context, NonNull::new_unchecked(cqe))
+                            .map(Some)
+                    };
+                }
+                libc::EINTR => continue,
+                libc::ETIME => return Ok(None),
+                libc::EAGAIN => return Err(Error::WaitCqeAgain),
+                errno => return Err(Error::WaitCqeError(io::Error::from_raw_os_error(errno))),
+            }
+        }
+    }
+
+    fn peek_cqe(&self) -> Option<NonNull<io_uring_cqe>> {
         let mut cqe = ptr::null_mut();
         unsafe {
-            let ret = io_uring_wait_cqe(self.ring.get(), &mut cqe);
-            if ret == 0 {
-                self.handle_cqe(context, NonNull::new_unchecked(cqe))
-                    .map(Some)
+            if io_uring_peek_cqe(self.ring.get(), &mut cqe) == 0 {
+                NonNull::new(cqe)
             } else {
-                Err(Error::WaitCqeError(io::Error::from_raw_os_error(-ret)))
+                None
             }
         }
     }
 
     fn handle_cqe(&self, context: &mut UringContext, cqe: NonNull<io_uring_cqe>) -> Result<u64> {
-        context.state.submitted_count -= 1;
-
         unsafe {
             let res = cqe.as_ref().res;
+            let flags = cqe.as_ref().flags;
+            let more = flags & IORING_CQE_F_MORE != 0;
             let id = io_uring_cqe_get_data64(cqe.as_ptr());
             io_uring_cqe_seen(self.ring.get(), cqe.as_ptr());
             assert_ne!(id, 0);
 
-            match context.state.map.entry(id) {
-                Entry::Vacant(_) => Err(Error::InternalError(format!(
-                    "no entry in the state map for id {}",
-                    id
-                ))),
-                Entry::Occupied(mut op) => {
-                    match op.get().status {
-                        OperationStatus::Cancelled => {
-                            op.remove();
+            let state = &mut *context.state;
+            let op = match state.map.get_mut(id) {
+                Some(op) => op,
+                // A message whose receive handle was dropped; nobody expects it anymore.
+                None => return Ok(id),
+            };
+            if matches!(op.kind, UringOperationKind::MsgRingReceive) {
+                state.expected_msgs -= 1;
+            } else if !more {
+                // A multishot operation stays in flight until its last completion.
+                state.submitted_count -= 1;
+            }
+
+            match op.status {
+                OperationStatus::Cancelled => {
+                    op.kind.discard(res);
+                    if !more {
+                        state.map.remove(id);
+                    }
+                }
+                _ if op.kind.is_multishot() => {
+                    op.kind.complete(res);
+                    match &mut op.status {
+                        OperationStatus::Multishot(completions) => {
+                            completions.push_back((res, flags))
+                        }
+                        status => {
+                            *status = OperationStatus::Multishot(VecDeque::from([(res, flags)]))
                         }
-                        _ => op.get_mut().status = OperationStatus::Completed(res),
                     }
-                    Ok(id)
                 }
+                OperationStatus::Notifying(sent, sent_flags) => {
+                    // The notification of a zero-copy send; the buffer is released.
+                    op.status = OperationStatus::Completed(sent, sent_flags);
+                    op.kind.complete(sent);
+                }
+                _ if more => {
+                    // A zero-copy send keeps the buffer until its notification.
+                    op.status = OperationStatus::Notifying(res, flags);
+                }
+                _ => {
+                    op.status = OperationStatus::Completed(res, flags);
+                    op.kind.complete(res);
+                }
+            }
+
+            #[cfg(feature = "async")]
+            if let Some(waker) = state.wakers.remove(&id) {
+                waker.wake();
             }
+            Ok(id)
         }
     }
 
     fn wait_for(&self, context: &mut UringContext, id: u64) -> Result<()> {
+        if let Some(UringOperation {
+            kind: UringOperationKind::MsgRingReceive,
+            ..
+        }) = context.state.map.get(id)
+        {
+            // The message is not counted as submitted; wait for it all the same.
+            while self.wait_cqe(context)? != id {}
+            return Ok(());
+        }
+
+        if context.state.unsubmitted.contains(&id) {
+            // The completions of the operations in flight may never arrive.
+            self.submit_with_context(context)?;
+        }
+
         while let Some(new_id) = self.wait_single_cqe(context)? {
             if id == new_id {
                 return Ok(());
@@ -208,10 +1503,102 @@ impl Uring {
             }
         }
 
-        Err(Error::InternalError(format!(
-            "wait_for({}) could not find the operation with the given id",
-            id
-        )))
+        Err(Error::CompletionNotFound(id))
+    }
+
+    /// Waits until the operation `id` is completed, including the notification of a
+    /// zero-copy send.
+    fn wait_completed(&self, context: &mut UringContext, id: u64) -> Result<()> {
+        loop {
+            self.wait_for(context, id)?;
+            match context.state.map.get(id) {
+                Some(UringOperation {
+                    status: OperationStatus::Notifying(..),
+                    ..
+                }) => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Requests the kernel to cancel the operation `id` and submits the request.
+    ///
+    /// The completion of the cancellation itself is discarded.
+    fn cancel(&self, context: &mut UringContext, id: u64) -> Result<()> {
+        let sqe = self.sqe(context)?;
+        let cancel_id = context.state.map.insert(UringOperation {
+            status: OperationStatus::Cancelled,
+            kind: UringOperationKind::Cancel,
+            borrowed: false,
+            tag: None,
+        });
+
+        unsafe {
+            io_uring_prep_cancel64(sqe.as_ptr(), id, 0);
+        }
+        self.set_id(context, sqe, cancel_id);
+
+        self.submit_with_context(context)?;
+        Ok(())
+    }
+
+    /// Submits pending SQEs and requests the kernel to cancel every operation that
+    /// has not completed yet, returning the number of targeted operations.
+    fn cancel_inflight(&self, context: &mut UringContext) -> Result<usize> {
+        self.submit_with_context(context)?;
+        let ids = context
+            .state
+            .map
+            .iter()
+            .filter(|(_, op)| {
+                !matches!(
+                    op.kind,
+                    UringOperationKind::Cancel
+                        | UringOperationKind::LinkTimeout(_)
+                        | UringOperationKind::MsgRingReceive
+                ) && (matches!(
+                    op.status,
+                    OperationStatus::Ongoing | OperationStatus::Cancelled
+                ) || op.is_armed_multishot())
+            })
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        for &id in &ids {
+            self.cancel(context, id)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Prepares a timeout for the previously prepared, linked SQE.
+    ///
+    /// The completion of the timeout itself is discarded.
+    fn link_timeout(&self, context: &mut UringContext, timeout: Duration) -> Result<()> {
+        let sqe = self.sqe(context)?;
+        // The kernel reads the timespec on submission, so the entry keeps it alive.
+        let mut ts = Box::new(__kernel_timespec {
+            tv_sec: timeout.as_secs() as _,
+            tv_nsec: timeout.subsec_nanos() as _,
+        });
+
+        unsafe {
+            io_uring_prep_link_timeout(sqe.as_ptr(), &mut *ts, 0);
+        }
+        let id = context.state.map.insert(UringOperation {
+            status: OperationStatus::Cancelled,
+            kind: UringOperationKind::LinkTimeout(ts),
+            borrowed: false,
+            tag: None,
+        });
+        self.set_id(context, sqe, id);
+        Ok(())
+    }
+
+    /// Sets `id` as the `user_data` of `sqe`, the last SQE taken from the SQ.
+    fn set_id(&self, context: &mut UringContext, sqe: NonNull<io_uring_sqe>, id: u64) {
+        unsafe {
+            io_uring_sqe_set_data64(sqe.as_ptr(), id);
+        }
+        context.state.unsubmitted.push_back(id);
     }
 
     fn sqe(&self, context: &mut UringContext) -> Result<NonNull<io_uring_sqe>> {
@@ -220,13 +1607,21 @@ impl Uring {
             match NonNull::new(sqe) {
                 Some(sqe) => Ok(sqe),
                 None => {
-                    self.submit_with_context(context)?;
+                    self.make_room(context)?;
                     NonNull::new(io_uring_get_sqe(self.ring.get())).ok_or(Error::GetSqeError)
                 }
             }
         }
     }
 
+    /// Makes room in the SQ according to the `SqFullPolicy`.
+    fn make_room(&self, context: &mut UringContext) -> Result<()> {
+        match self.sq_full_policy {
+            SqFullPolicy::AutoSubmit => self.submit_with_context(context).map(|_| ()),
+            SqFullPolicy::Error => Err(Error::SqFull),
+        }
+    }
+
     fn submit_with_context(&self, context: &mut UringContext) -> Result<usize> {
         let submitted = unsafe {
             let ret = io_uring_submit(self.ring.get());
@@ -240,6 +1635,9 @@ impl Uring {
             ret as usize
         };
 
+        // The kernel consumes the SQ in order, even if it stops early.
+        let unsubmitted = &mut context.state.unsubmitted;
+        unsubmitted.drain(..submitted.min(unsubmitted.len()));
         context.state.submitted_count += submitted;
         Ok(submitted)
     }
@@ -252,47 +1650,179 @@ impl Uring {
     where
         Sqe<T>: UringSqe<'a>,
     {
+        uring_sqe.validate()?;
         let sqe = self.sqe(context)?;
-        context.state.id_gen += 1;
-        let id = context.state.id_gen;
 
         uring_sqe.prepare(sqe);
+        let borrowed = uring_sqe.borrows() || uring_sqe.fd_borrowed;
+        let (flag, tag) = (uring_sqe.flag, uring_sqe.tag);
+        let id = context.state.map.insert(UringOperation {
+            status: OperationStatus::Ongoing,
+            kind: uring_sqe.into(),
+            borrowed,
+            tag,
+        });
         unsafe {
-            io_uring_sqe_set_flags(sqe.as_ptr(), uring_sqe.flag);
-            io_uring_sqe_set_data64(sqe.as_ptr(), id);
+            io_uring_sqe_set_flags(sqe.as_ptr(), flag);
         }
-
-        context.state.map.insert(
-            id,
-            UringOperation {
-                status: OperationStatus::Ongoing,
-                kind: uring_sqe.into(),
-            },
-        );
+        self.set_id(context, sqe, id);
 
         Ok(<Sqe<T> as UringSqe<'a>>::Handle::new(id, self))
     }
 }
 
+/// Id of an operation, unique among the operations of a [`Uring`] in flight.
+///
+/// Returned by the `id` method of the handles, e.g. [`ReadHandle::id`], and yielded by
+/// [`Uring::completions`]. It is the `user_data` of the SQE, so it can be used to
+/// correlate an operation with external logs or traces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OperationId(pub(crate) u64);
+
+impl From<OperationId> for u64 {
+    fn from(id: OperationId) -> u64 {
+        id.0
+    }
+}
+
+impl fmt::Display for OperationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Outcome of [`Uring::submit_with_status`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubmitStatus {
+    submitted: usize,
+    pending: usize,
+}
+
+impl SubmitStatus {
+    /// Returns the number of entries submitted by the call.
+    pub fn submitted(&self) -> usize {
+        self.submitted
+    }
+
+    /// Returns the number of entries still in the SQ after the call.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Returns true if the SQ is empty, i.e. every prepared entry is submitted.
+    pub fn is_complete(&self) -> bool {
+        self.pending == 0
+    }
+}
+
+/// Iterator over the ready completions, returned by [`Uring::completions`].
+pub struct CompletionIter<'a> {
+    ring: &'a Uring,
+}
+
+impl<'a> Iterator for CompletionIter<'a> {
+    type Item = Result<(OperationId, i32)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut context = match self.ring.context() {
+            Ok(context) => context,
+            Err(e) => return Some(Err(e)),
+        };
+        loop {
+            let cqe = self.ring.peek_cqe()?;
+            let (res, flags) = unsafe { (cqe.as_ref().res, cqe.as_ref().flags) };
+            match self.ring.handle_cqe(&mut context, cqe) {
+                // Skip the completions of dropped handles and internal cancellations.
+                Ok(id) if !context.state.map.contains_key(id) => continue,
+                // The result of a zero-copy send is yielded with its first completion.
+                Ok(_) if flags & IORING_CQE_F_NOTIF != 0 => continue,
+                result => return Some(result.map(|id| (OperationId(id), res))),
+            }
+        }
+    }
+}
+
 struct UringOperation {
     status: OperationStatus,
     kind: UringOperationKind,
+    /// The operation borrows memory from the caller, so its handle must not be
+    /// dropped before the completion is observed.
+    borrowed: bool,
+    /// Tag attached with [`Sqe::tag`].
+    tag: Option<u64>,
+}
+
+impl UringOperation {
+    /// Returns `true` if this is a multishot operation that may post more completions.
+    fn is_armed_multishot(&self) -> bool {
+        if !self.kind.is_multishot() {
+            return false;
+        }
+        match &self.status {
+            OperationStatus::Ongoing => true,
+            OperationStatus::Multishot(completions) => match completions.back() {
+                Some((_, flags)) => flags & IORING_CQE_F_MORE != 0,
+                None => true,
+            },
+            _ => false,
+        }
+    }
 }
 
 enum OperationStatus {
     /// Pending or submitted and hasn't been observed in the CQ.
     Ongoing,
-    /// Observed in the CQ.
-    Completed(i32),
+    /// Observed in the CQ, with the result and the flags of the completion.
+    Completed(i32, u32),
+    /// Zero-copy send whose result is observed in the CQ, with the result and the
+    /// flags, waiting for the notification that the kernel released the buffer.
+    Notifying(i32, u32),
+    /// Multishot operation with completions observed in the CQ but not yet taken.
+    Multishot(VecDeque<(i32, u32)>),
     /// Cancelled; the user is no longer interested in the result.
     Cancelled,
 }
 
+/// The fd of the ring, e.g. to register it with an external epoll instance.
+///
+/// The `Uring` owns the fd: it must not be closed by the user, since dropping the
+/// `Uring` closes it in `io_uring_queue_exit`.
+impl AsRawFd for Uring {
+    fn as_raw_fd(&self) -> RawFd {
+        unsafe { (*self.ring.get()).ring_fd }
+    }
+}
+
 impl Drop for Uring {
     fn drop(&mut self) {
-        let mut context = self.context();
-        while let Ok(Some(_id)) = self.wait_single_cqe(&mut context) {}
-        unsafe { io_uring_queue_exit(self.ring.get()) }
+        // Nothing else can borrow the state while the ring is dropped.
+        let mut context = self.context().unwrap();
+        // Operations that never complete on their own, e.g. a `recvmsg` on an idle
+        // socket, would otherwise block the loop below forever.
+        let _ = self.cancel_inflight(&mut context);
+        let deadline = self.drop_timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let next = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    self.wait_single_cqe_timeout(&mut context, left)
+                }
+                None => self.wait_single_cqe(&mut context),
+            };
+            if !matches!(next, Ok(Some(_))) {
+                break;
+            }
+        }
+        if context.state.submitted_count > 0 {
+            // The kernel may still write to the memory of the remaining operations.
+            mem::forget(context.state.map.drain());
+        }
+        unsafe {
+            if context.state.ring_fd_registered {
+                io_uring_unregister_ring_fd(self.ring.get());
+            }
+            io_uring_queue_exit(self.ring.get())
+        }
     }
 }
 
@@ -314,7 +1844,7 @@ mod test {
             let h = ring
                 .prepare_read(Sqe::new(ReadData {
                     fd: f.as_raw_fd(),
-                    buf: UringBuf::Vec(vec![0; 128]),
+                    buf: UringBuf::Vec(vec![0; 128]).into(),
                     offset: 0,
                 }))
                 .unwrap();
@@ -328,4 +1858,300 @@ mod test {
             assert_eq!(&buf.as_slice()[..len], s.as_bytes());
         }
     }
+
+    #[test]
+    fn test_drop_handle_in_flight() {
+        let ring = Uring::new(8).unwrap();
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(b"hello, world\n").unwrap();
+
+        let handle = ring
+            .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+            .unwrap();
+        ring.submit().unwrap();
+        drop(handle);
+
+        // The cancelled operation keeps its buffer until the completion is observed.
+        assert_eq!(ring.state.borrow().map.len(), 1);
+        let mut context = ring.context().unwrap();
+        while ring.wait_single_cqe(&mut context).unwrap().is_some() {}
+        assert!(context.state.map.is_empty());
+    }
+
+    #[test]
+    fn test_unexpected_operation_kind() {
+        let res: Result<crate::result::ReadResult> =
+            (0, 0, UringOperationKind::Fsync(FsyncData { fd: 0 })).try_into();
+        assert!(matches!(
+            res,
+            Err(Error::UnexpectedOperationKind {
+                expected: "ReadResult"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_discard_unexpected_cqe() {
+        let ring = Uring::new(8).unwrap();
+        let handle = ring.prepare_nop(Sqe::nop()).unwrap();
+        ring.submit().unwrap();
+        let mut context = ring.context().unwrap();
+        let id = u64::from(handle.id());
+        // Pretend that the completion is a message nobody expects anymore.
+        context.state.map.remove(id);
+        context.state.submitted_count -= 1;
+        assert_eq!(ring.wait_cqe(&mut context).unwrap(), id);
+        assert_eq!(context.state.submitted_count, 0);
+        assert!(context.state.map.is_empty());
+    }
+
+    #[test]
+    fn test_wait_missing_operation() {
+        let ring = Uring::new(8).unwrap();
+        let handle = ring.prepare_nop(Sqe::nop()).unwrap();
+        ring.submit().unwrap();
+        let id = u64::from(handle.id());
+        {
+            let mut context = ring.context().unwrap();
+            assert_eq!(ring.wait_single_cqe(&mut context).unwrap(), Some(id));
+            context.state.map.remove(id);
+        }
+        assert!(matches!(handle.wait(), Err(Error::MissingOperation(i)) if i == id));
+    }
+
+    #[test]
+    fn test_drain() {
+        let ring = Uring::new(8).unwrap();
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(b"hello, world").unwrap();
+
+        let read = ring
+            .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+            .unwrap();
+        let nops = (0..4)
+            .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+            .collect::<Vec<_>>();
+        ring.drain().unwrap();
+        {
+            let state = ring.state.borrow();
+            assert!(state.map.is_empty());
+            assert_eq!(state.submitted_count, 0);
+        }
+        assert!(matches!(read.wait(), Err(Error::MissingOperation(_))));
+        drop(nops);
+    }
+
+    #[test]
+    fn test_partial_submit_accounting() {
+        let ring = Uring::new(4).unwrap();
+        // Preparing more than the SQ depth submits the earlier entries implicitly.
+        let handles = (0..10)
+            .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+            .collect::<Vec<_>>();
+        {
+            let state = ring.state.borrow();
+            assert_eq!(state.unsubmitted.len(), ring.sq_ready());
+            assert_eq!(state.submitted_count + state.unsubmitted.len(), 10);
+            let pending = handles[10 - ring.sq_ready()..]
+                .iter()
+                .map(|h| h.id().0)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                state.unsubmitted.iter().copied().collect::<Vec<_>>(),
+                pending
+            );
+        }
+
+        // Waiting on an unsubmitted operation submits it.
+        let mut handles = handles.into_iter().rev();
+        assert!(handles
+            .next()
+            .unwrap()
+            .wait()
+            .unwrap()
+            .as_io_result()
+            .is_ok());
+        assert!(ring.state.borrow().unsubmitted.is_empty());
+        for h in handles {
+            assert!(h.wait().unwrap().as_io_result().is_ok());
+        }
+        let state = ring.state.borrow();
+        assert_eq!(state.submitted_count, 0);
+        assert_eq!(ring.cq_ready(), 0);
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let e: io::Error = Error::SubmitError(io::Error::from_raw_os_error(libc::EBADF)).into();
+        assert_eq!(e.raw_os_error(), Some(libc::EBADF));
+
+        let e: io::Error = Error::SqFull.into();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        assert_eq!(e.to_string(), "submission queue is full");
+    }
+
+    #[test]
+    fn test_submit_and_wait_one() {
+        let ring = Uring::new(8).unwrap();
+        let mut f = tempfile::tempfile().unwrap();
+        f.write_all(b"hello").unwrap();
+        assert!(ring.submit_and_wait_one().unwrap().is_none());
+
+        let h = ring
+            .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 8]), 0))
+            .unwrap();
+        assert_eq!(ring.submit_and_wait_one().unwrap(), Some((h.id(), 5)));
+        assert!(h.observed());
+        assert_eq!(&h.wait().unwrap().into_buf().as_slice()[..5], b"hello");
+    }
+
+    #[test]
+    fn test_busy() {
+        let ring = Uring::new(8).unwrap();
+        let mut h = ring.prepare_nop(Sqe::nop()).unwrap();
+        {
+            // As if a callback called into the ring while a completion is processed.
+            let _context = ring.context().unwrap();
+            assert!(matches!(ring.submit(), Err(Error::Busy)));
+            assert!(matches!(ring.prepare_nop(Sqe::nop()), Err(Error::Busy)));
+            assert!(matches!(h.try_result(), Err(Error::Busy)));
+        }
+        ring.submit().unwrap();
+        h.wait().unwrap();
+    }
+
+    #[test]
+    fn test_drop_handle_while_busy() {
+        let ring = Uring::new(8).unwrap();
+        let h = ring.prepare_nop(Sqe::nop()).unwrap();
+        {
+            let _context = ring.context().unwrap();
+            assert!(!h.observed());
+            assert!(matches!(ring.inflight(), Err(Error::Busy)));
+            // Released by the next borrow of the state instead of panicking.
+            drop(h);
+        }
+        assert_eq!(ring.dropped.borrow().len(), 1);
+        ring.submit().unwrap();
+        assert!(ring.dropped.borrow().is_empty());
+        ring.drain().unwrap();
+        assert_eq!(ring.state.borrow().map.len(), 0);
+    }
+
+    #[test]
+    fn test_probe_once() {
+        let ring = Uring::new(8).unwrap();
+        assert!(ring.supported.get().is_none());
+        let _ = ring.check_supported(OpKind::Nop);
+        let probe = ring.supported.get().map(|p| p as *const Probe);
+        let _ = ring.check_supported(OpKind::Ftruncate);
+        assert_eq!(ring.supported.get().map(|p| p as *const Probe), probe);
+    }
+
+    #[test]
+    fn test_as_raw_fd() {
+        let ring = Uring::new(8).unwrap();
+        let fd = ring.as_raw_fd();
+        assert_eq!(fd, ring.ring_fd());
+        assert!(unsafe { libc::fcntl(fd, libc::F_GETFD) } >= 0);
+    }
+
+    #[test]
+    fn test_completion_not_found() {
+        let ring = Uring::new(8).unwrap();
+        let err = ring.wait_for(&mut ring.context().unwrap(), 42).unwrap_err();
+        assert!(matches!(err, Error::CompletionNotFound(42)));
+        assert_eq!(
+            err.to_string(),
+            "internal error: wait_for(42) could not find the operation with the given id"
+        );
+    }
+
+    #[test]
+    fn test_sq_space_left() {
+        let ring = Uring::new(4).unwrap();
+        assert_eq!(ring.sq_space_left(), 4);
+        let handles = (0..4)
+            .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ring.sq_space_left(), 0);
+
+        // The SQ is full, so the next prepare flushes it.
+        let last = ring.prepare_nop(Sqe::nop()).unwrap();
+        assert_eq!(ring.sq_space_left(), 3);
+        assert_eq!(ring.submit().unwrap(), 1);
+        for h in handles.into_iter().chain([last]) {
+            assert!(h.wait().unwrap().as_io_result().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_sq_ready() {
+        let ring = Uring::new(8).unwrap();
+        assert_eq!(ring.sq_ready(), 0);
+        let handle = ring.prepare_nop(Sqe::nop()).unwrap();
+        assert_eq!(ring.sq_ready(), 1);
+        assert_eq!(ring.sq_space_left(), 7);
+        ring.submit().unwrap();
+        assert_eq!(ring.sq_ready(), 0);
+        assert_eq!(ring.sq_space_left(), 8);
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+
+    #[test]
+    fn test_cq_ready() {
+        let ring = Uring::new(8).unwrap();
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(b"hello, world\n").unwrap();
+
+        let handles = (0..3)
+            .map(|_| {
+                ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ring.cq_ready(), 0);
+        assert_eq!(ring.submit().unwrap(), 3);
+        while ring.cq_ready() < 3 {
+            std::thread::yield_now();
+        }
+        assert_eq!(ring.cq_ready(), 3);
+
+        for h in handles {
+            assert!(h.wait().unwrap().as_io_result().is_ok());
+        }
+        assert_eq!(ring.cq_ready(), 0);
+    }
+
+    #[test]
+    fn test_completions() {
+        let ring = Uring::new(8).unwrap();
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(b"hello, world\n").unwrap();
+
+        let handles = (0..3)
+            .map(|_| {
+                ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ring.completions().count(), 0);
+        assert_eq!(ring.submit().unwrap(), 3);
+        while ring.cq_ready() < 3 {
+            std::thread::yield_now();
+        }
+
+        let mut completed = ring.completions().map(|c| c.unwrap()).collect::<Vec<_>>();
+        completed.sort_unstable();
+        let mut ids = handles.iter().map(|h| h.id()).collect::<Vec<_>>();
+        ids.sort_unstable();
+        assert_eq!(completed.iter().map(|c| c.0).collect::<Vec<_>>(), ids);
+        assert!(completed.iter().all(|c| c.1 == 13));
+        assert_eq!(ring.cq_ready(), 0);
+
+        for h in handles {
+            assert!(h.observed());
+            assert_eq!(h.wait().unwrap().as_io_result().unwrap(), 13);
+        }
+    }
 }
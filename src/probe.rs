@@ -0,0 +1,211 @@
+//! Detection of operations and features supported by the running kernel.
+use std::{fmt, ptr::NonNull};
+
+use uring_sys2::*;
+
+/// Operations this library can issue.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OpKind {
+    Read,
+    Write,
+    Fsync,
+    Fdatasync,
+    Madvise,
+    Fadvise,
+    Ftruncate,
+    Accept,
+    Shutdown,
+    Socket,
+    Close,
+    Recv,
+    Sendmsg,
+    Recvmsg,
+    Rename,
+    Unlink,
+    Mkdir,
+    Symlink,
+    Link,
+    Nop,
+    Splice,
+    Tee,
+    MsgRing,
+    EpollCtl,
+    Waitid,
+    Setxattr,
+    Getxattr,
+    SyncFileRange,
+    SendZc,
+    Openat,
+    Bind,
+    Listen,
+}
+
+impl OpKind {
+    fn opcode(self) -> i32 {
+        let opcode = match self {
+            OpKind::Read => IORING_OP_READ,
+            OpKind::Write => IORING_OP_WRITE,
+            OpKind::Fsync | OpKind::Fdatasync => IORING_OP_FSYNC,
+            OpKind::Madvise => IORING_OP_MADVISE,
+            OpKind::Fadvise => IORING_OP_FADVISE,
+            OpKind::Ftruncate => IORING_OP_FTRUNCATE,
+            OpKind::Accept => IORING_OP_ACCEPT,
+            OpKind::Shutdown => IORING_OP_SHUTDOWN,
+            OpKind::Socket => IORING_OP_SOCKET,
+            OpKind::Close => IORING_OP_CLOSE,
+            OpKind::Recv => IORING_OP_RECV,
+            OpKind::Sendmsg => IORING_OP_SENDMSG,
+            OpKind::Recvmsg => IORING_OP_RECVMSG,
+            OpKind::Rename => IORING_OP_RENAMEAT,
+            OpKind::Unlink => IORING_OP_UNLINKAT,
+            OpKind::Mkdir => IORING_OP_MKDIRAT,
+            OpKind::Symlink => IORING_OP_SYMLINKAT,
+            OpKind::Link => IORING_OP_LINKAT,
+            OpKind::Nop => IORING_OP_NOP,
+            OpKind::Splice => IORING_OP_SPLICE,
+            OpKind::Tee => IORING_OP_TEE,
+            OpKind::MsgRing => IORING_OP_MSG_RING,
+            OpKind::EpollCtl => IORING_OP_EPOLL_CTL,
+            OpKind::Waitid => IORING_OP_WAITID,
+            OpKind::Setxattr => IORING_OP_SETXATTR,
+            OpKind::Getxattr => IORING_OP_GETXATTR,
+            OpKind::SyncFileRange => IORING_OP_SYNC_FILE_RANGE,
+            OpKind::SendZc => IORING_OP_SEND_ZC,
+            OpKind::Openat => IORING_OP_OPENAT,
+            OpKind::Bind => IORING_OP_BIND,
+            OpKind::Listen => IORING_OP_LISTEN,
+        };
+        opcode as i32
+    }
+}
+
+/// Operations supported by the running kernel.
+///
+/// Created by [`Uring::probe`](crate::Uring::probe).
+pub struct Probe(NonNull<io_uring_probe>);
+
+impl Probe {
+    pub(crate) fn new(probe: NonNull<io_uring_probe>) -> Probe {
+        Probe(probe)
+    }
+
+    /// Returns true if the running kernel supports the operation.
+    ///
+    /// Equivalent to `io_uring_opcode_supported`.
+    pub fn supports(&self, op: OpKind) -> bool {
+        unsafe { io_uring_opcode_supported(self.0.as_ptr(), op.opcode()) != 0 }
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        unsafe { io_uring_free_probe(self.0.as_ptr()) }
+    }
+}
+
+/// Features of `io_uring` supported by the running kernel, as reported in
+/// `io_uring_params.features` when the ring was created.
+///
+/// Created by [`Uring::features`](crate::Uring::features).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Features(u32);
+
+impl Features {
+    pub(crate) fn new(bits: u32) -> Features {
+        Features(bits)
+    }
+
+    /// Returns the raw `IORING_FEAT_*` bitset.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+macro_rules! define_features {
+    ($([$name:ident, $flag:ident, $doc:expr],)*) => {
+        impl Features {
+            $(
+                #[doc = $doc]
+                pub fn $name(&self) -> bool {
+                    self.0 & $flag != 0
+                }
+            )*
+        }
+
+        impl fmt::Debug for Features {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct("Features")
+                    $(.field(stringify!($name), &self.$name()))*
+                    .finish()
+            }
+        }
+    };
+}
+
+define_features!(
+    [
+        single_mmap,
+        IORING_FEAT_SINGLE_MMAP,
+        "The SQ and the CQ are mapped with a single `mmap` (`IORING_FEAT_SINGLE_MMAP`)."
+    ],
+    [
+        nodrop,
+        IORING_FEAT_NODROP,
+        "Completions are never dropped when the CQ overflows (`IORING_FEAT_NODROP`)."
+    ],
+    [
+        submit_stable,
+        IORING_FEAT_SUBMIT_STABLE,
+        "Data for an SQE is consumed on submission (`IORING_FEAT_SUBMIT_STABLE`)."
+    ],
+    [
+        rw_cur_pos,
+        IORING_FEAT_RW_CUR_POS,
+        "Offset -1 reads or writes at the current file position (`IORING_FEAT_RW_CUR_POS`)."
+    ],
+    [
+        cur_personality,
+        IORING_FEAT_CUR_PERSONALITY,
+        "Operations use the credentials of the submitter (`IORING_FEAT_CUR_PERSONALITY`)."
+    ],
+    [
+        fast_poll,
+        IORING_FEAT_FAST_POLL,
+        "Operations on sockets poll internally instead of using a worker (`IORING_FEAT_FAST_POLL`)."
+    ],
+    [
+        poll_32bits,
+        IORING_FEAT_POLL_32BITS,
+        "Poll masks have 32 bits (`IORING_FEAT_POLL_32BITS`)."
+    ],
+    [
+        sqpoll_nonfixed,
+        IORING_FEAT_SQPOLL_NONFIXED,
+        "SQPOLL works without registered files (`IORING_FEAT_SQPOLL_NONFIXED`)."
+    ],
+    [
+        ext_arg,
+        IORING_FEAT_EXT_ARG,
+        "Waiting accepts a timeout argument (`IORING_FEAT_EXT_ARG`)."
+    ],
+    [
+        native_workers,
+        IORING_FEAT_NATIVE_WORKERS,
+        "Workers are native threads of the process (`IORING_FEAT_NATIVE_WORKERS`)."
+    ],
+    [
+        rsrc_tags,
+        IORING_FEAT_RSRC_TAGS,
+        "Registered resources can be tagged (`IORING_FEAT_RSRC_TAGS`)."
+    ],
+    [
+        cqe_skip,
+        IORING_FEAT_CQE_SKIP,
+        "`IOSQE_CQE_SKIP_SUCCESS` is supported (`IORING_FEAT_CQE_SKIP`)."
+    ],
+    [
+        linked_file,
+        IORING_FEAT_LINKED_FILE,
+        "Files of linked operations are resolved when they start (`IORING_FEAT_LINKED_FILE`)."
+    ],
+);
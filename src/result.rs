@@ -1,7 +1,17 @@
 //! Result of asynchronous operation.
-use std::io;
+use std::{
+    fmt, io,
+    os::unix::io::{FromRawFd, OwnedFd, RawFd},
+};
 
-use crate::{buf::UringBuf, sqe::*, Error};
+use uring_sys2::{IORING_CQE_BUFFER_SHIFT, IORING_CQE_F_BUFFER, IORING_CQE_F_MORE};
+
+use crate::{
+    buf::{ResultBuf, UringBuf},
+    handle::ReadHandle,
+    sqe::*,
+    Error, Uring,
+};
 
 /// A trait for objects that represent the result of io_uring operations.
 pub trait IoResult: Into<UringResult> {
@@ -10,14 +20,56 @@ pub trait IoResult: Into<UringResult> {
 
     /// Converts the result into [`io::Result`](std::io::Result).
     fn as_io_result(&self) -> io::Result<Self::Output>;
+
+    /// Returns the flags of the completion (`IORING_CQE_F_*`).
+    fn flags(&self) -> u32;
+
+    /// Returns the tag attached with [`Sqe::tag`](crate::sqe::Sqe::tag), if any.
+    fn tag(&self) -> Option<u64>;
+
+    /// Returns true if the operation failed with `EAGAIN` (`EWOULDBLOCK`), e.g. a
+    /// non-blocking `recv(2)` with no data available, so that it can be retried later.
+    fn would_block(&self) -> bool {
+        matches!(self.as_io_result(), Err(e) if e.raw_os_error() == Some(libc::EAGAIN))
+    }
 }
 
 /// [`IoResult`](IoResult) for operations that owns the [`UringBuf`](crate::buf::UringBuf).
 pub trait BufIoResult: IoResult {
+    /// Returns the buffer of the operation.
+    ///
+    /// A borrowed slice is not returned, as its borrow may already have ended; an empty
+    /// `UringBuf::Vec` is returned instead.
     fn into_buf(self) -> UringBuf;
+
+    /// Returns the result and the buffer at once.
+    ///
+    /// The buffer is returned even if the operation failed.
+    ///
+    /// ```rust
+    /// # use std::{io::Write, os::unix::io::AsRawFd};
+    /// use aluring::{buf::UringBuf, result::BufIoResult, sqe::Sqe, Uring};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let mut f = tempfile::tempfile()?;
+    /// # f.write_all(b"hello")?;
+    /// let uring = Uring::new(8)?;
+    /// let handle = uring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::uninit(16), 0))?;
+    /// uring.submit()?;
+    /// let (len, buf) = handle.wait()?.into_parts();
+    /// assert_eq!(len?, 5);
+    /// assert_eq!(buf.as_slice(), b"hello");
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn into_parts(self) -> (io::Result<Self::Output>, UringBuf) {
+        let res = self.as_io_result();
+        (res, self.into_buf())
+    }
 }
 
 /// Result of io_uring operations.
+#[derive(Debug)]
 pub enum UringResult {
     /// Result of asynchronous `read(2)`.
     Read(ReadResult),
@@ -29,6 +81,148 @@ pub enum UringResult {
     Fdatasync(FdatasyncResult),
     /// Result of asynchronous `madvise(2)`.
     Madvise(MadviseResult),
+    /// Result of asynchronous `posix_fadvise(2)`.
+    Fadvise(FadviseResult),
+    /// Result of asynchronous `ftruncate(2)`.
+    Ftruncate(FtruncateResult),
+    /// Result of asynchronous `accept4(2)`.
+    Accept(AcceptResult),
+    /// Result of asynchronous `shutdown(2)`.
+    Shutdown(ShutdownResult),
+    /// Result of asynchronous `socket(2)`.
+    Socket(SocketResult),
+    /// Result of asynchronous `close(2)`.
+    Close(CloseResult),
+    /// Result of asynchronous `recv(2)`.
+    Recv(RecvResult),
+    /// Result of asynchronous `sendmsg(2)`.
+    Sendmsg(SendmsgResult),
+    /// Result of asynchronous `recvmsg(2)`.
+    Recvmsg(RecvmsgResult),
+    /// Result of asynchronous `renameat2(2)`.
+    Rename(RenameResult),
+    /// Result of asynchronous `unlinkat(2)`.
+    Unlink(UnlinkResult),
+    /// Result of asynchronous `mkdirat(2)`.
+    Mkdir(MkdirResult),
+    /// Result of asynchronous `symlinkat(2)`.
+    Symlink(SymlinkResult),
+    /// Result of asynchronous `linkat(2)`.
+    Link(LinkResult),
+    /// Result of asynchronous no-op.
+    Nop(NopResult),
+    /// Result of asynchronous `splice(2)`.
+    Splice(SpliceResult),
+    /// Result of asynchronous `tee(2)`.
+    Tee(TeeResult),
+    /// Result of asynchronous message to another ring.
+    MsgRing(MsgRingResult),
+    /// Message from another ring.
+    MsgRingReceive(MsgRingReceiveResult),
+    /// Result of asynchronous `epoll_ctl(2)`.
+    EpollCtl(EpollCtlResult),
+    /// Result of asynchronous `waitid(2)`.
+    Waitid(WaitidResult),
+    /// Result of asynchronous `setxattr(2)`.
+    Setxattr(SetxattrResult),
+    /// Result of asynchronous `getxattr(2)`.
+    Getxattr(GetxattrResult),
+    /// Result of asynchronous `sync_file_range(2)`.
+    SyncFileRange(SyncFileRangeResult),
+    /// Result of asynchronous zero-copy `send(2)`.
+    SendZc(SendZcResult),
+    /// Result of asynchronous `openat(2)`.
+    Openat(OpenatResult),
+    /// Result of asynchronous `bind(2)`.
+    Bind(BindResult),
+    /// Result of asynchronous `listen(2)`.
+    Listen(ListenResult),
+}
+
+macro_rules! define_result_accessors {
+    ($([$var:ident, $as:ident, $result:ident],)*) => {
+        impl UringResult {
+            $(
+                #[doc = concat!("Returns the [`", stringify!($result), "`] if this is one.")]
+                pub fn $as(&self) -> Option<&$result> {
+                    match self {
+                        UringResult::$var(result) => Some(result),
+                        _ => None,
+                    }
+                }
+            )*
+
+            /// Returns the flags of the completion (`IORING_CQE_F_*`).
+            pub fn flags(&self) -> u32 {
+                match self {
+                    $(UringResult::$var(result) => result.flags(),)*
+                }
+            }
+
+            /// Returns the tag attached with [`Sqe::tag`](crate::sqe::Sqe::tag), if any.
+            pub fn tag(&self) -> Option<u64> {
+                match self {
+                    $(UringResult::$var(result) => result.tag(),)*
+                }
+            }
+        }
+    };
+}
+
+define_result_accessors!(
+    [Read, as_read, ReadResult],
+    [Write, as_write, WriteResult],
+    [Fsync, as_fsync, FsyncResult],
+    [Fdatasync, as_fdatasync, FdatasyncResult],
+    [Madvise, as_madvise, MadviseResult],
+    [Fadvise, as_fadvise, FadviseResult],
+    [Ftruncate, as_ftruncate, FtruncateResult],
+    [Accept, as_accept, AcceptResult],
+    [Shutdown, as_shutdown, ShutdownResult],
+    [Socket, as_socket, SocketResult],
+    [Close, as_close, CloseResult],
+    [Recv, as_recv, RecvResult],
+    [Sendmsg, as_sendmsg, SendmsgResult],
+    [Recvmsg, as_recvmsg, RecvmsgResult],
+    [Rename, as_rename, RenameResult],
+    [Unlink, as_unlink, UnlinkResult],
+    [Mkdir, as_mkdir, MkdirResult],
+    [Symlink, as_symlink, SymlinkResult],
+    [Link, as_link, LinkResult],
+    [Nop, as_nop, NopResult],
+    [Splice, as_splice, SpliceResult],
+    [Tee, as_tee, TeeResult],
+    [MsgRing, as_msg_ring, MsgRingResult],
+    [MsgRingReceive, as_msg_ring_receive, MsgRingReceiveResult],
+    [EpollCtl, as_epoll_ctl, EpollCtlResult],
+    [Waitid, as_waitid, WaitidResult],
+    [Setxattr, as_setxattr, SetxattrResult],
+    [Getxattr, as_getxattr, GetxattrResult],
+    [SyncFileRange, as_sync_file_range, SyncFileRangeResult],
+    [SendZc, as_send_zc, SendZcResult],
+    [Openat, as_openat, OpenatResult],
+    [Bind, as_bind, BindResult],
+    [Listen, as_listen, ListenResult],
+);
+
+impl UringResult {
+    /// Returns the number of bytes transferred by a successful read, write, send,
+    /// receive, `splice(2)` or `tee(2)`.
+    ///
+    /// Returns `None` for other operations and if the operation failed.
+    pub fn bytes_transferred(&self) -> Option<usize> {
+        match self {
+            UringResult::Read(result) => result.as_io_result().ok(),
+            UringResult::Write(result) => result.as_io_result().ok(),
+            UringResult::Recv(result) => result.as_io_result().ok(),
+            UringResult::Sendmsg(result) => result.as_io_result().ok(),
+            UringResult::Recvmsg(result) => result.as_io_result().ok(),
+            UringResult::SendZc(result) => result.as_io_result().ok(),
+            UringResult::Splice(result) => result.as_io_result().ok(),
+            UringResult::Tee(result) => result.as_io_result().ok(),
+            _ => None,
+        }
+    }
 }
 
 macro_rules! try_io {
@@ -42,16 +236,35 @@ macro_rules! try_io {
 }
 
 macro_rules! define_buf_io_result {
-    ($result:ident, $variant:ident, $data:ident, $doc:expr) => {
+    ($result:ident, $variant:ident, $data:ident, $doc:expr $(, $field:ident: $ty:ty)*) => {
         #[doc = $doc]
         pub struct $result {
-            buf: UringBuf,
+            buf: ResultBuf,
             res: i32,
+            flags: u32,
+            tag: Option<u64>,
+            $($field: $ty,)*
+        }
+
+        impl fmt::Debug for $result {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($result))
+                    .field("res", &self.res)
+                    .field("flags", &self.flags)
+                    .field("tag", &self.tag)
+                    .field("buf_len", &self.buf.len())
+                    .finish()
+            }
         }
 
         impl $result {
-            pub(crate) fn new(buf: UringBuf, res: i32) -> $result {
-                $result { buf, res }
+            pub(crate) fn new(buf: ResultBuf, res: i32, flags: u32 $(, $field: $ty)*) -> $result {
+                $result { buf, res, flags, tag: None $(, $field)* }
+            }
+
+            pub(crate) fn with_tag(mut self, tag: Option<u64>) -> $result {
+                self.tag = tag;
+                self
             }
         }
 
@@ -61,11 +274,19 @@ macro_rules! define_buf_io_result {
             fn as_io_result(&self) -> io::Result<Self::Output> {
                 try_io!(self.res, self.res as usize)
             }
+
+            fn flags(&self) -> u32 {
+                self.flags
+            }
+
+            fn tag(&self) -> Option<u64> {
+                self.tag
+            }
         }
 
         impl BufIoResult for $result {
             fn into_buf(self) -> UringBuf {
-                self.buf
+                self.buf.into_buf()
             }
         }
 
@@ -75,18 +296,17 @@ macro_rules! define_buf_io_result {
             }
         }
 
-        impl TryInto<$result> for (i32, UringOperationKind) {
+        impl TryInto<$result> for (i32, u32, UringOperationKind) {
             type Error = Error;
 
             fn try_into(self) -> Result<$result, Self::Error> {
                 match self {
-                    (res, UringOperationKind::$variant($data { buf, .. })) => {
-                        Ok($result::new(buf, res))
+                    (res, flags, UringOperationKind::$variant($data { buf, $($field,)* .. })) => {
+                        Ok($result::new(buf.into(), res, flags $(, $field)*))
                     }
-                    _ => Err(Error::InternalError(String::from(concat!(
-                        "invalid conversion from UringOperationKind to ",
-                        stringify!($result)
-                    )))),
+                    _ => Err(Error::UnexpectedOperationKind {
+                        expected: stringify!($result),
+                    }),
                 }
             }
         }
@@ -95,14 +315,26 @@ macro_rules! define_buf_io_result {
 
 macro_rules! define_empty_io_result {
     ($result:ident, $variant:ident, $data:ident, $doc:expr) => {
+        #[derive(Debug)]
         #[doc = $doc]
         pub struct $result {
             res: i32,
+            flags: u32,
+            tag: Option<u64>,
         }
 
         impl $result {
-            pub(crate) fn new(res: i32) -> $result {
-                $result { res }
+            pub(crate) fn new(res: i32, flags: u32) -> $result {
+                $result {
+                    res,
+                    flags,
+                    tag: None,
+                }
+            }
+
+            pub(crate) fn with_tag(mut self, tag: Option<u64>) -> $result {
+                self.tag = tag;
+                self
             }
         }
 
@@ -118,18 +350,167 @@ macro_rules! define_empty_io_result {
             fn as_io_result(&self) -> io::Result<Self::Output> {
                 try_io!(self.res, ())
             }
+
+            fn flags(&self) -> u32 {
+                self.flags
+            }
+
+            fn tag(&self) -> Option<u64> {
+                self.tag
+            }
         }
 
-        impl TryInto<$result> for (i32, UringOperationKind) {
+        impl TryInto<$result> for (i32, u32, UringOperationKind) {
             type Error = Error;
 
             fn try_into(self) -> Result<$result, Self::Error> {
                 match self {
-                    (res, UringOperationKind::$variant($data { .. })) => Ok($result::new(res)),
-                    _ => Err(Error::InternalError(String::from(concat!(
-                        "invalid conversion from UringOperationKind to ",
-                        stringify!($result)
-                    )))),
+                    (res, flags, UringOperationKind::$variant($data { .. })) => {
+                        Ok($result::new(res, flags))
+                    }
+                    _ => Err(Error::UnexpectedOperationKind {
+                        expected: stringify!($result),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! define_fd_io_result {
+    ($result:ident, $variant:ident, $data:ident, $doc:expr) => {
+        #[derive(Debug)]
+        #[doc = $doc]
+        pub struct $result {
+            res: i32,
+            flags: u32,
+            tag: Option<u64>,
+        }
+
+        impl $result {
+            pub(crate) fn new(res: i32, flags: u32) -> $result {
+                $result {
+                    res,
+                    flags,
+                    tag: None,
+                }
+            }
+
+            pub(crate) fn with_tag(mut self, tag: Option<u64>) -> $result {
+                self.tag = tag;
+                self
+            }
+
+            /// Takes ownership of the new file descriptor, so that it is closed when
+            /// the returned `OwnedFd` is dropped.
+            ///
+            /// Must not be used for a direct descriptor, whose result is the index of
+            /// a slot in the registered file table.
+            pub fn into_owned_fd(self) -> io::Result<OwnedFd> {
+                let fd = self.as_io_result()?;
+                Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+            }
+        }
+
+        impl Into<UringResult> for $result {
+            fn into(self) -> UringResult {
+                UringResult::$variant(self)
+            }
+        }
+
+        impl IoResult for $result {
+            /// The new file descriptor, which the caller is responsible for closing.
+            type Output = RawFd;
+
+            fn as_io_result(&self) -> io::Result<Self::Output> {
+                try_io!(self.res, self.res)
+            }
+
+            fn flags(&self) -> u32 {
+                self.flags
+            }
+
+            fn tag(&self) -> Option<u64> {
+                self.tag
+            }
+        }
+
+        impl TryInto<$result> for (i32, u32, UringOperationKind) {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$result, Self::Error> {
+                match self {
+                    (res, flags, UringOperationKind::$variant($data { .. })) => {
+                        Ok($result::new(res, flags))
+                    }
+                    _ => Err(Error::UnexpectedOperationKind {
+                        expected: stringify!($result),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! define_len_io_result {
+    ($result:ident, $variant:ident, $data:ident, $doc:expr) => {
+        #[derive(Debug)]
+        #[doc = $doc]
+        pub struct $result {
+            res: i32,
+            flags: u32,
+            tag: Option<u64>,
+        }
+
+        impl $result {
+            pub(crate) fn new(res: i32, flags: u32) -> $result {
+                $result {
+                    res,
+                    flags,
+                    tag: None,
+                }
+            }
+
+            pub(crate) fn with_tag(mut self, tag: Option<u64>) -> $result {
+                self.tag = tag;
+                self
+            }
+        }
+
+        impl Into<UringResult> for $result {
+            fn into(self) -> UringResult {
+                UringResult::$variant(self)
+            }
+        }
+
+        impl IoResult for $result {
+            /// The number of bytes transferred.
+            type Output = usize;
+
+            fn as_io_result(&self) -> io::Result<Self::Output> {
+                try_io!(self.res, self.res as usize)
+            }
+
+            fn flags(&self) -> u32 {
+                self.flags
+            }
+
+            fn tag(&self) -> Option<u64> {
+                self.tag
+            }
+        }
+
+        impl TryInto<$result> for (i32, u32, UringOperationKind) {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$result, Self::Error> {
+                match self {
+                    (res, flags, UringOperationKind::$variant($data { .. })) => {
+                        Ok($result::new(res, flags))
+                    }
+                    _ => Err(Error::UnexpectedOperationKind {
+                        expected: stringify!($result),
+                    }),
                 }
             }
         }
@@ -148,12 +529,85 @@ define_buf_io_result!(
     ReadData,
     "Result of asynchronous `read(2)`"
 );
+
+impl ReadResult {
+    /// Returns true if the read hit end of file, i.e. `res` is 0.
+    ///
+    /// `as_io_result` returns `Ok(0)` in this case, like [`std::io::Read::read`]; a
+    /// loop reading until the buffer is full must stop here instead of reading again
+    /// at the same offset. Reading into an empty buffer also returns 0.
+    pub fn is_eof(&self) -> bool {
+        self.res == 0
+    }
+
+    /// Prepares the next `read(2)` of `fd` at `offset` into the same buffer.
+    ///
+    /// The whole buffer is available to the read again; a `BytesMut` is cleared first,
+    /// and a `Vec` truncated by the previous read is grown back to its capacity. Fails
+    /// with [`Error::InvalidSqe`] for a read into a borrowed slice or a
+    /// [`UringBuf::Raw`], whose memory the new read cannot keep alive.
+    pub fn recycle(
+        self,
+        ring: &Uring,
+        fd: RawFd,
+        offset: impl Into<Offset>,
+    ) -> crate::Result<ReadHandle> {
+        let mut buf = match self.buf.into_owned() {
+            Some(UringBuf::Raw { .. }) | None => {
+                return Err(Error::InvalidSqe("borrowed buffer cannot be recycled"))
+            }
+            Some(buf) => buf,
+        };
+        buf.clear();
+        ring.prepare_read(Sqe::read(fd, buf, offset))
+    }
+}
 define_buf_io_result!(
     WriteResult,
     Write,
     WriteData,
-    "Result of asynchronous `write(2)`"
+    "Result of asynchronous `write(2)`",
+    fd: RawFd,
+    offset: u64
 );
+
+impl WriteResult {
+    /// Returns the number of bytes written, or 0 if the write failed.
+    pub fn transferred(&self) -> usize {
+        self.res.max(0) as usize
+    }
+
+    /// Returns true if the write succeeded but wrote less than the whole buffer.
+    pub fn is_short(&self) -> bool {
+        self.res >= 0 && (self.res as usize) < self.buf.len()
+    }
+
+    /// Returns an `Sqe` writing the unwritten tail of a short write at the advanced
+    /// offset, or `None` if the write was not short.
+    ///
+    /// Also returns `None` for a buffer passed as [`UringBuf::Raw`] or as a borrowed
+    /// slice, whose memory the new `Sqe` cannot keep alive; use
+    /// [`Uring::prepare_write_all`](crate::Uring::prepare_write_all) for those.
+    pub fn remaining(self) -> Option<Sqe<WriteData<'static>>> {
+        if !self.is_short() || matches!(self.buf, ResultBuf::Owned(UringBuf::Raw { .. })) {
+            return None;
+        }
+        let n = self.transferred();
+        let buf = self.buf.into_owned()?;
+        // `u64::MAX` writes at the current file position, which advances by itself.
+        let offset = if self.offset == u64::MAX {
+            u64::MAX
+        } else {
+            self.offset + n as u64
+        };
+        Some(Sqe::write(self.fd, buf.advance(n), offset))
+    }
+
+    /// Replaces the byte count with the total of all writes of a `write_all`.
+    pub(crate) fn set_transferred(&mut self, total: usize) {
+        self.res = total as i32;
+    }
+}
 define_empty_io_result!(
     FsyncResult,
     Fsync,
@@ -166,3 +620,598 @@ define_empty_io_result!(
     FdatasyncData,
     "Result of asynchronous `fdatasync(2)`"
 );
+define_empty_io_result!(
+    FadviseResult,
+    Fadvise,
+    FadviseData,
+    "Result of asynchronous `posix_fadvise(2)`"
+);
+define_empty_io_result!(
+    FtruncateResult,
+    Ftruncate,
+    FtruncateData,
+    "Result of asynchronous `ftruncate(2)`"
+);
+
+/// Result of asynchronous `accept4(2)`.
+#[derive(Debug)]
+pub struct AcceptResult {
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+}
+
+impl AcceptResult {
+    pub(crate) fn new(res: i32, flags: u32) -> AcceptResult {
+        AcceptResult {
+            res,
+            flags,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> AcceptResult {
+        self.tag = tag;
+        self
+    }
+
+    /// Returns true if more completions follow, i.e. the multishot accept is still armed.
+    pub fn more(&self) -> bool {
+        self.flags & IORING_CQE_F_MORE != 0
+    }
+}
+
+impl Into<UringResult> for AcceptResult {
+    fn into(self) -> UringResult {
+        UringResult::Accept(self)
+    }
+}
+
+impl IoResult for AcceptResult {
+    /// The accepted socket, which the caller is responsible for closing.
+    type Output = RawFd;
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        try_io!(self.res, self.res)
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+define_empty_io_result!(
+    ShutdownResult,
+    Shutdown,
+    ShutdownData,
+    "Result of asynchronous `shutdown(2)`"
+);
+define_fd_io_result!(
+    SocketResult,
+    Socket,
+    SocketData,
+    "Result of asynchronous `socket(2)`"
+);
+define_empty_io_result!(
+    CloseResult,
+    Close,
+    CloseData,
+    "Result of asynchronous `close(2)`"
+);
+/// Result of asynchronous `recv(2)` with buffer selection.
+///
+/// Pass it to [`BufRing::get`](crate::buf::BufRing::get) to access the received data.
+#[derive(Debug)]
+pub struct RecvResult {
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+    bgid: u16,
+}
+
+impl RecvResult {
+    pub(crate) fn new(res: i32, flags: u32, bgid: u16) -> RecvResult {
+        RecvResult {
+            res,
+            flags,
+            tag: None,
+            bgid,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> RecvResult {
+        self.tag = tag;
+        self
+    }
+
+    /// Returns the id of the buffer the kernel selected, if any.
+    pub fn buffer_id(&self) -> Option<u16> {
+        if self.flags & IORING_CQE_F_BUFFER != 0 {
+            Some((self.flags >> IORING_CQE_BUFFER_SHIFT) as u16)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the buffer group the buffer was selected from.
+    pub fn bgid(&self) -> u16 {
+        self.bgid
+    }
+
+    /// Returns true if more completions follow, i.e. the multishot recv is still armed.
+    pub fn more(&self) -> bool {
+        self.flags & IORING_CQE_F_MORE != 0
+    }
+
+    /// Returns true if the peer closed the connection, i.e. `res` is 0.
+    ///
+    /// No buffer is selected in this case.
+    pub fn is_eof(&self) -> bool {
+        self.res == 0
+    }
+}
+
+impl Into<UringResult> for RecvResult {
+    fn into(self) -> UringResult {
+        UringResult::Recv(self)
+    }
+}
+
+impl IoResult for RecvResult {
+    type Output = usize;
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        try_io!(self.res, self.res as usize)
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+impl TryInto<RecvResult> for (i32, u32, UringOperationKind) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<RecvResult, Self::Error> {
+        match self {
+            (res, flags, UringOperationKind::Recv { bgid }) => {
+                Ok(RecvResult::new(res, flags, bgid))
+            }
+            _ => Err(Error::UnexpectedOperationKind {
+                expected: "RecvResult",
+            }),
+        }
+    }
+}
+/// Result of asynchronous `sendmsg(2)`.
+pub struct SendmsgResult {
+    msg: Box<MsgHdr>,
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+}
+
+impl SendmsgResult {
+    pub(crate) fn new(msg: Box<MsgHdr>, res: i32, flags: u32) -> SendmsgResult {
+        SendmsgResult {
+            msg,
+            res,
+            flags,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> SendmsgResult {
+        self.tag = tag;
+        self
+    }
+}
+
+impl fmt::Debug for SendmsgResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendmsgResult")
+            .field("res", &self.res)
+            .field("flags", &self.flags)
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl Into<UringResult> for SendmsgResult {
+    fn into(self) -> UringResult {
+        UringResult::Sendmsg(self)
+    }
+}
+
+impl IoResult for SendmsgResult {
+    type Output = usize;
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        try_io!(self.res, self.res as usize)
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+impl BufIoResult for SendmsgResult {
+    fn into_buf(self) -> UringBuf {
+        self.msg.into_buf()
+    }
+}
+
+impl TryInto<SendmsgResult> for (i32, u32, UringOperationKind) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<SendmsgResult, Self::Error> {
+        match self {
+            (res, flags, UringOperationKind::Sendmsg(data)) => {
+                Ok(SendmsgResult::new(data.msg, res, flags))
+            }
+            _ => Err(Error::UnexpectedOperationKind {
+                expected: "SendmsgResult",
+            }),
+        }
+    }
+}
+/// Result of asynchronous `recvmsg(2)`.
+pub struct RecvmsgResult {
+    msg: Box<MsgHdr>,
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+}
+
+impl RecvmsgResult {
+    pub(crate) fn new(msg: Box<MsgHdr>, res: i32, flags: u32) -> RecvmsgResult {
+        RecvmsgResult {
+            msg,
+            res,
+            flags,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> RecvmsgResult {
+        self.tag = tag;
+        self
+    }
+
+    /// Returns the received ancillary data, to be parsed with the `CMSG_*` macros.
+    pub fn control(&self) -> &[u8] {
+        if self.res < 0 {
+            return &[];
+        }
+        self.msg.control()
+    }
+
+    /// Returns the file descriptors received in `SCM_RIGHTS` messages.
+    ///
+    /// The caller is responsible for closing them.
+    pub fn fds(&self) -> Vec<RawFd> {
+        if self.res < 0 {
+            return Vec::new();
+        }
+        self.msg.fds()
+    }
+}
+
+impl fmt::Debug for RecvmsgResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvmsgResult")
+            .field("res", &self.res)
+            .field("flags", &self.flags)
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl Into<UringResult> for RecvmsgResult {
+    fn into(self) -> UringResult {
+        UringResult::Recvmsg(self)
+    }
+}
+
+impl IoResult for RecvmsgResult {
+    type Output = usize;
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        try_io!(self.res, self.res as usize)
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+impl BufIoResult for RecvmsgResult {
+    fn into_buf(self) -> UringBuf {
+        self.msg.into_buf()
+    }
+}
+
+impl TryInto<RecvmsgResult> for (i32, u32, UringOperationKind) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<RecvmsgResult, Self::Error> {
+        match self {
+            (res, flags, UringOperationKind::Recvmsg(data)) => {
+                Ok(RecvmsgResult::new(data.msg, res, flags))
+            }
+            _ => Err(Error::UnexpectedOperationKind {
+                expected: "RecvmsgResult",
+            }),
+        }
+    }
+}
+define_empty_io_result!(
+    RenameResult,
+    Rename,
+    RenameData,
+    "Result of asynchronous `renameat2(2)`"
+);
+define_empty_io_result!(
+    UnlinkResult,
+    Unlink,
+    UnlinkData,
+    "Result of asynchronous `unlinkat(2)`"
+);
+define_empty_io_result!(
+    MkdirResult,
+    Mkdir,
+    MkdirData,
+    "Result of asynchronous `mkdirat(2)`"
+);
+define_empty_io_result!(
+    SymlinkResult,
+    Symlink,
+    SymlinkData,
+    "Result of asynchronous `symlinkat(2)`"
+);
+define_empty_io_result!(
+    LinkResult,
+    Link,
+    LinkData,
+    "Result of asynchronous `linkat(2)`"
+);
+define_empty_io_result!(NopResult, Nop, NopData, "Result of asynchronous no-op");
+define_len_io_result!(
+    SpliceResult,
+    Splice,
+    SpliceData,
+    "Result of asynchronous `splice(2)`"
+);
+define_len_io_result!(TeeResult, Tee, TeeData, "Result of asynchronous `tee(2)`");
+define_empty_io_result!(
+    MsgRingResult,
+    MsgRing,
+    MsgRingData,
+    "Result of asynchronous message to another ring"
+);
+
+/// Message from another ring, posted by
+/// [`Uring::prepare_msg_ring`](crate::Uring::prepare_msg_ring).
+#[derive(Debug)]
+pub struct MsgRingReceiveResult {
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+}
+
+impl MsgRingReceiveResult {
+    pub(crate) fn new(res: i32, flags: u32) -> MsgRingReceiveResult {
+        MsgRingReceiveResult {
+            res,
+            flags,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> MsgRingReceiveResult {
+        self.tag = tag;
+        self
+    }
+
+    /// Returns the `result` the sender passed.
+    pub fn value(&self) -> i32 {
+        self.res
+    }
+}
+
+impl Into<UringResult> for MsgRingReceiveResult {
+    fn into(self) -> UringResult {
+        UringResult::MsgRingReceive(self)
+    }
+}
+
+/// The value is chosen by the sender and is not an error code, so this never fails.
+impl IoResult for MsgRingReceiveResult {
+    type Output = i32;
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        Ok(self.res)
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+impl TryInto<MsgRingReceiveResult> for (i32, u32, UringOperationKind) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<MsgRingReceiveResult, Self::Error> {
+        match self {
+            (res, flags, UringOperationKind::MsgRingReceive) => {
+                Ok(MsgRingReceiveResult::new(res, flags))
+            }
+            _ => Err(Error::UnexpectedOperationKind {
+                expected: "MsgRingReceiveResult",
+            }),
+        }
+    }
+}
+define_empty_io_result!(
+    EpollCtlResult,
+    EpollCtl,
+    EpollCtlData,
+    "Result of asynchronous `epoll_ctl(2)`"
+);
+
+/// Result of asynchronous `waitid(2)`.
+pub struct WaitidResult {
+    siginfo: Box<libc::siginfo_t>,
+    res: i32,
+    flags: u32,
+    tag: Option<u64>,
+}
+
+impl WaitidResult {
+    pub(crate) fn new(siginfo: Box<libc::siginfo_t>, res: i32, flags: u32) -> WaitidResult {
+        WaitidResult {
+            siginfo,
+            res,
+            flags,
+            tag: None,
+        }
+    }
+
+    pub(crate) fn with_tag(mut self, tag: Option<u64>) -> WaitidResult {
+        self.tag = tag;
+        self
+    }
+
+    /// Returns the `siginfo_t` filled by the kernel.
+    ///
+    /// With `WNOHANG` and no child in a waitable state, `si_pid` is zero.
+    pub fn siginfo(&self) -> &libc::siginfo_t {
+        &self.siginfo
+    }
+
+    /// Returns the pid of the child, i.e. `si_pid`.
+    pub fn pid(&self) -> libc::pid_t {
+        unsafe { self.siginfo.si_pid() }
+    }
+
+    /// Returns the status of the child, i.e. `si_status`.
+    ///
+    /// This is the exit status if [`siginfo`](Self::siginfo) has `si_code` of
+    /// `CLD_EXITED`, and the signal otherwise.
+    pub fn status(&self) -> i32 {
+        unsafe { self.siginfo.si_status() }
+    }
+}
+
+impl fmt::Debug for WaitidResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitidResult")
+            .field("res", &self.res)
+            .field("flags", &self.flags)
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl Into<UringResult> for WaitidResult {
+    fn into(self) -> UringResult {
+        UringResult::Waitid(self)
+    }
+}
+
+impl IoResult for WaitidResult {
+    type Output = ();
+
+    fn as_io_result(&self) -> io::Result<Self::Output> {
+        try_io!(self.res, ())
+    }
+
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+impl TryInto<WaitidResult> for (i32, u32, UringOperationKind) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<WaitidResult, Self::Error> {
+        match self {
+            (res, flags, UringOperationKind::Waitid(data)) => {
+                Ok(WaitidResult::new(data.siginfo, res, flags))
+            }
+            _ => Err(Error::UnexpectedOperationKind {
+                expected: "WaitidResult",
+            }),
+        }
+    }
+}
+define_buf_io_result!(
+    SetxattrResult,
+    Setxattr,
+    SetxattrData,
+    "Result of asynchronous `setxattr(2)`"
+);
+define_buf_io_result!(
+    GetxattrResult,
+    Getxattr,
+    GetxattrData,
+    "Result of asynchronous `getxattr(2)`"
+);
+
+define_empty_io_result!(
+    SyncFileRangeResult,
+    SyncFileRange,
+    SyncFileRangeData,
+    "Result of asynchronous `sync_file_range(2)`
+
+Success does not make the data durable: `sync_file_range(2)` neither flushes the
+metadata needed to find the data nor the disk write cache. Use `fsync(2)` or
+`fdatasync(2)` where durability matters."
+);
+define_buf_io_result!(
+    SendZcResult,
+    SendZc,
+    SendZcData,
+    "Result of asynchronous zero-copy `send(2)`"
+);
+define_fd_io_result!(
+    OpenatResult,
+    Openat,
+    OpenatData,
+    "Result of asynchronous `openat(2)`"
+);
+define_empty_io_result!(
+    BindResult,
+    Bind,
+    BindData,
+    "Result of asynchronous `bind(2)`"
+);
+define_empty_io_result!(
+    ListenResult,
+    Listen,
+    ListenData,
+    "Result of asynchronous `listen(2)`"
+);
@@ -0,0 +1,146 @@
+//! Arena of the operations of a ring, keyed by the `user_data` of their SQEs.
+//!
+//! A key holds the index of a slot in the lower 32 bits and the generation of the
+//! slot in the upper 32 bits. The generation changes whenever a slot is freed, so a
+//! stale key, e.g. from the completion of a forgotten operation, never matches the
+//! operation that reuses the slot. Generations start at 1, so 0 is never a key.
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+pub(crate) struct Slab<T> {
+    slots: Vec<Slot<T>>,
+    /// Indices of the free slots.
+    free: Vec<u32>,
+    len: usize,
+}
+
+fn key(index: usize, generation: u32) -> u64 {
+    (generation as u64) << 32 | index as u64
+}
+
+impl<T> Slab<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Slab<T> {
+        Slab {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores `value` in a free slot and returns its key.
+    pub(crate) fn insert(&mut self, value: T) -> u64 {
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.value = Some(value);
+                key(index as usize, slot.generation)
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 1,
+                    value: Some(value),
+                });
+                key(self.slots.len() - 1, 1)
+            }
+        }
+    }
+
+    fn slot(&self, key: u64) -> Option<&Slot<T>> {
+        self.slots
+            .get(key as u32 as usize)
+            .filter(|slot| slot.generation == (key >> 32) as u32)
+    }
+
+    fn slot_mut(&mut self, key: u64) -> Option<&mut Slot<T>> {
+        self.slots
+            .get_mut(key as u32 as usize)
+            .filter(|slot| slot.generation == (key >> 32) as u32)
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<&T> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    pub(crate) fn get_mut(&mut self, key: u64) -> Option<&mut T> {
+        self.slot_mut(key)?.value.as_mut()
+    }
+
+    pub(crate) fn contains_key(&self, key: u64) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub(crate) fn remove(&mut self, key: u64) -> Option<T> {
+        let slot = self.slot_mut(key)?;
+        let value = slot.value.take()?;
+        // Invalidate the key; skip 0 so that no key is ever 0.
+        slot.generation = slot.generation.wrapping_add(1).max(1);
+        self.free.push(key as u32);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.value
+                .as_ref()
+                .map(|value| (key(index, slot.generation), value))
+        })
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    /// Removes every value, returning them with their keys.
+    pub(crate) fn drain(&mut self) -> Vec<(u64, T)> {
+        let keys = self.iter().map(|(key, _)| key).collect::<Vec<_>>();
+        keys.into_iter()
+            .filter_map(|key| self.remove(key).map(|value| (key, value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stale_key() {
+        let mut slab = Slab::with_capacity(4);
+        let a = slab.insert("a");
+        assert_ne!(a, 0);
+        assert_eq!(slab.remove(a), Some("a"));
+
+        // The slot is reused, but the old key does not match the new value.
+        let b = slab.insert("b");
+        assert_eq!(a as u32, b as u32);
+        assert_ne!(a, b);
+        assert_eq!(slab.get(a), None);
+        assert_eq!(slab.remove(a), None);
+        assert_eq!(slab.get(b), Some(&"b"));
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut slab = Slab::with_capacity(4);
+        let keys = (0..3).map(|i| slab.insert(i)).collect::<Vec<_>>();
+        slab.remove(keys[1]);
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.drain(), vec![(keys[0], 0), (keys[2], 2)]);
+        assert!(slab.is_empty());
+        assert!(keys.iter().all(|key| !slab.contains_key(*key)));
+    }
+}
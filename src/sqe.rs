@@ -1,53 +1,206 @@
 //! Submission queue entry of `io_uring`.
-use std::{os::unix::io::RawFd, ptr::NonNull};
+use std::{
+    ffi::CString,
+    mem,
+    net::SocketAddr,
+    os::unix::io::{AsFd, AsRawFd, RawFd},
+    ptr,
+    ptr::NonNull,
+    slice,
+};
 
 use uring_sys2::*;
 
 use crate::{
-    handle::Handler, FdatasyncHandle, FsyncHandle, MadviseHandle, ReadHandle, UringBuf, WriteHandle,
+    buf::{BufRing, UringAdviseBuf, UringReadBuf, UringWriteBuf},
+    handle::{
+        BindHandle, CloseHandle, EpollCtlHandle, GetxattrHandle, Handler, LinkHandle, ListenHandle,
+        MkdirHandle, MsgRingHandle, MultishotAcceptHandle, MultishotRecvHandle, NopHandle,
+        OpenatHandle, RecvHandle, RecvmsgHandle, RenameHandle, SendZcHandle, SendmsgHandle,
+        SetxattrHandle, ShutdownHandle, SocketHandle, SpliceHandle, SymlinkHandle,
+        SyncFileRangeHandle, TeeHandle, UnlinkHandle, WaitidHandle,
+    },
+    Error, FadviseHandle, FdatasyncHandle, FsyncHandle, FtruncateHandle, MadviseHandle, ReadHandle,
+    Result, UringBuf, WriteHandle,
 };
 
 pub(crate) trait UringSqe<'a>: Into<UringOperationKind> {
     type Handle: Handler<'a>;
 
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>);
+
+    /// Checks the inputs before the SQE is taken from the ring.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns true if the operation borrows memory from the caller.
+    fn borrows(&self) -> bool {
+        false
+    }
 }
 
 /// Submission queue entry (SQE) of `io_uring`.
 pub struct Sqe<T> {
     pub(crate) flag: u32,
+    /// The fd is borrowed from the caller, so the handle must not be dropped before
+    /// the completion is observed.
+    pub(crate) fd_borrowed: bool,
+    /// Tag chosen by the caller, returned with the result.
+    pub(crate) tag: Option<u64>,
     pub(crate) data: T,
 }
 
 /// Data type for io_uring operations.
 pub trait UringData {}
 
-impl Sqe<ReadData> {
+/// File offset of a read or write.
+///
+/// A plain `u64` converts into [`Offset::At`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offset {
+    /// At the given offset, like `pread(2)`.
+    At(u64),
+    /// At the current file position, which advances by the bytes transferred, like
+    /// `read(2)`. Pipes and sockets support only this.
+    Current,
+}
+
+impl From<u64> for Offset {
+    fn from(offset: u64) -> Offset {
+        Offset::At(offset)
+    }
+}
+
+impl From<Offset> for u64 {
+    /// Returns the offset passed to the kernel, where `u64::MAX`, i.e. `-1`, stands for
+    /// [`Offset::Current`].
+    fn from(offset: Offset) -> u64 {
+        match offset {
+            Offset::At(offset) => offset,
+            Offset::Current => u64::MAX,
+        }
+    }
+}
+
+impl<'a> Sqe<ReadData<'a>> {
     /// Creates a new `Sqe` for `read(2)`.
-    pub fn read(fd: RawFd, buf: UringBuf, offset: u64) -> Sqe<ReadData> {
+    pub fn read(
+        fd: RawFd,
+        buf: impl Into<UringReadBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Sqe<ReadData<'a>> {
+        let offset: Offset = offset.into();
         Sqe {
             flag: 0,
-            data: ReadData { fd, buf, offset },
+            fd_borrowed: false,
+            tag: None,
+            data: ReadData {
+                fd,
+                buf: buf.into(),
+                offset: offset.into(),
+            },
+        }
+    }
+}
+
+impl<'a> Sqe<ReadData<'a>> {
+    /// Creates a new `Sqe` for `read(2)` on a borrowed fd.
+    ///
+    /// The handle of the operation holds the borrow, so the fd cannot be closed while
+    /// the kernel may still use it:
+    ///
+    /// ```compile_fail
+    /// use std::fs::File;
+    /// use aluring::{buf::UringBuf, sqe::Sqe, Uring};
+    ///
+    /// let uring = Uring::new(8).unwrap();
+    /// let f = File::open("/dev/zero").unwrap();
+    /// let handle = uring
+    ///     .prepare_read(Sqe::read_fd(&f, UringBuf::Vec(vec![0; 16]), 0))
+    ///     .unwrap();
+    /// drop(f);
+    /// handle.wait().unwrap();
+    /// ```
+    pub fn read_fd(
+        fd: &'a impl AsFd,
+        buf: impl Into<UringReadBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Sqe<ReadData<'a>> {
+        Sqe {
+            fd_borrowed: true,
+            ..Sqe::read(fd.as_fd().as_raw_fd(), buf, offset)
         }
     }
 }
 
-impl Sqe<WriteData> {
+impl<'a> Sqe<WriteData<'a>> {
     /// Creates a new `Sqe` for `write(2)`.
-    pub fn write(fd: RawFd, buf: UringBuf, offset: u64) -> Sqe<WriteData> {
+    pub fn write(
+        fd: RawFd,
+        buf: impl Into<UringWriteBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Sqe<WriteData<'a>> {
+        let offset: Offset = offset.into();
         Sqe {
             flag: 0,
-            data: WriteData { fd, buf, offset },
+            fd_borrowed: false,
+            tag: None,
+            data: WriteData {
+                fd,
+                buf: buf.into(),
+                offset: offset.into(),
+            },
+        }
+    }
+
+    /// Creates a new `Sqe` for `write(2)` on a borrowed fd.
+    ///
+    /// The handle of the operation holds the borrow, so the fd cannot be closed while
+    /// the kernel may still use it.
+    pub fn write_fd(
+        fd: &'a impl AsFd,
+        buf: impl Into<UringWriteBuf<'a>>,
+        offset: impl Into<Offset>,
+    ) -> Sqe<WriteData<'a>> {
+        Sqe {
+            fd_borrowed: true,
+            ..Sqe::write(fd.as_fd().as_raw_fd(), buf, offset)
         }
     }
 }
 
 impl Sqe<MadviseData> {
     /// Creates a new `Sqe` for `madvise(2)`.
-    pub fn madvise(buf: UringBuf, advise: Madvise) -> Sqe<MadviseData> {
+    ///
+    /// Pass a [`MemRegion`](crate::buf::MemRegion) to advise on memory that is not
+    /// writable, e.g. a `PROT_READ` mapping.
+    pub fn madvise(buf: impl Into<UringAdviseBuf>, advise: Madvise) -> Sqe<MadviseData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: MadviseData {
+                buf: buf.into(),
+                advise,
+            },
+        }
+    }
+}
+
+impl Sqe<FadviseData> {
+    /// Creates a new `Sqe` for `posix_fadvise(2)`.
+    pub fn fadvise(fd: RawFd, offset: u64, len: u64, advice: i32) -> Sqe<FadviseData> {
         Sqe {
             flag: 0,
-            data: MadviseData { buf, advise },
+            fd_borrowed: false,
+            tag: None,
+            data: FadviseData {
+                fd,
+                offset,
+                len,
+                advice,
+            },
         }
     }
 }
@@ -57,6 +210,8 @@ impl Sqe<FsyncData> {
     pub fn fsync(fd: RawFd) -> Sqe<FsyncData> {
         Sqe {
             flag: 0,
+            fd_borrowed: false,
+            tag: None,
             data: FsyncData { fd },
         }
     }
@@ -67,15 +222,514 @@ impl Sqe<FdatasyncData> {
     pub fn fdatasync(fd: RawFd) -> Sqe<FdatasyncData> {
         Sqe {
             flag: 0,
+            fd_borrowed: false,
+            tag: None,
             data: FdatasyncData { fd },
         }
     }
 }
 
+impl Sqe<FtruncateData> {
+    /// Creates a new `Sqe` for `ftruncate(2)`.
+    pub fn ftruncate(fd: RawFd, len: u64) -> Sqe<FtruncateData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: FtruncateData { fd, len },
+        }
+    }
+}
+
+impl Sqe<MultishotAcceptData> {
+    /// Creates a new `Sqe` for multishot `accept4(2)`.
+    pub fn multishot_accept(fd: RawFd, flags: i32) -> Sqe<MultishotAcceptData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: MultishotAcceptData { fd, flags },
+        }
+    }
+}
+
+impl Sqe<ShutdownData> {
+    /// Creates a new `Sqe` for `shutdown(2)`.
+    pub fn shutdown(fd: RawFd, how: i32) -> Sqe<ShutdownData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: ShutdownData { fd, how },
+        }
+    }
+}
+
+impl Sqe<SocketData> {
+    /// Creates a new `Sqe` for `socket(2)`.
+    pub fn socket(domain: i32, type_: i32, protocol: i32, flags: u32) -> Sqe<SocketData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SocketData {
+                domain,
+                type_,
+                protocol,
+                flags,
+                direct: false,
+            },
+        }
+    }
+
+    /// Creates a new `Sqe` for `socket(2)` installing the socket in a free slot of the
+    /// registered file table instead of the fd table.
+    ///
+    /// The result is the index of the slot, to be used with [`Sqe::fixed_file`].
+    pub fn socket_direct(domain: i32, type_: i32, protocol: i32, flags: u32) -> Sqe<SocketData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SocketData {
+                domain,
+                type_,
+                protocol,
+                flags,
+                direct: true,
+            },
+        }
+    }
+}
+
+impl Sqe<CloseData> {
+    /// Creates a new `Sqe` for `close(2)`.
+    pub fn close(fd: RawFd) -> Sqe<CloseData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: CloseData { fd },
+        }
+    }
+}
+
+impl<'a> Sqe<RecvData<'a>> {
+    /// Creates a new `Sqe` for `recv(2)` into a buffer selected from `buf_ring`.
+    pub fn recv(fd: RawFd, buf_ring: &'a BufRing<'a>, flags: i32) -> Sqe<RecvData<'a>> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: RecvData {
+                fd,
+                buf_ring,
+                flags,
+            },
+        }
+    }
+}
+
+impl<'a> Sqe<MultishotRecvData<'a>> {
+    /// Creates a new `Sqe` for multishot `recv(2)` into buffers selected from `buf_ring`.
+    pub fn multishot_recv(
+        fd: RawFd,
+        buf_ring: &'a BufRing<'a>,
+        flags: i32,
+    ) -> Sqe<MultishotRecvData<'a>> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: MultishotRecvData {
+                fd,
+                buf_ring,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<SendmsgData> {
+    /// Creates a new `Sqe` for `sendmsg(2)` sending `buf` with the ancillary data
+    /// `control`, e.g. built by [`scm_rights`].
+    pub fn sendmsg(fd: RawFd, buf: UringBuf, control: &[u8], flags: i32) -> Sqe<SendmsgData> {
+        let mut msg = MsgHdr::new(buf, false, control.len());
+        msg.control_mut().copy_from_slice(control);
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SendmsgData { fd, flags, msg },
+        }
+    }
+}
+
+impl Sqe<RecvmsgData> {
+    /// Creates a new `Sqe` for `recvmsg(2)` receiving into `buf`, with room for
+    /// `control_len` bytes of ancillary data.
+    pub fn recvmsg(fd: RawFd, buf: UringBuf, control_len: usize, flags: i32) -> Sqe<RecvmsgData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: RecvmsgData {
+                fd,
+                flags,
+                msg: MsgHdr::new(buf, true, control_len),
+            },
+        }
+    }
+}
+
+impl Sqe<RenameData> {
+    /// Creates a new `Sqe` for `renameat2(2)`.
+    pub fn rename(
+        olddirfd: RawFd,
+        oldpath: CString,
+        newdirfd: RawFd,
+        newpath: CString,
+        flags: u32,
+    ) -> Sqe<RenameData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: RenameData {
+                olddirfd,
+                oldpath,
+                newdirfd,
+                newpath,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<UnlinkData> {
+    /// Creates a new `Sqe` for `unlinkat(2)`.
+    pub fn unlink(dirfd: RawFd, path: CString, flags: i32) -> Sqe<UnlinkData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: UnlinkData { dirfd, path, flags },
+        }
+    }
+}
+
+impl Sqe<MkdirData> {
+    /// Creates a new `Sqe` for `mkdirat(2)`.
+    pub fn mkdir(dirfd: RawFd, path: CString, mode: u32) -> Sqe<MkdirData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: MkdirData { dirfd, path, mode },
+        }
+    }
+}
+
+impl Sqe<SymlinkData> {
+    /// Creates a new `Sqe` for `symlinkat(2)`.
+    pub fn symlink(target: CString, newdirfd: RawFd, linkpath: CString) -> Sqe<SymlinkData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SymlinkData {
+                target,
+                newdirfd,
+                linkpath,
+            },
+        }
+    }
+}
+
+impl Sqe<LinkData> {
+    /// Creates a new `Sqe` for `linkat(2)`.
+    pub fn hard_link(
+        olddirfd: RawFd,
+        oldpath: CString,
+        newdirfd: RawFd,
+        newpath: CString,
+        flags: i32,
+    ) -> Sqe<LinkData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: LinkData {
+                olddirfd,
+                oldpath,
+                newdirfd,
+                newpath,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<NopData> {
+    /// Creates a new `Sqe` for a no-op.
+    pub fn nop() -> Sqe<NopData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: NopData {},
+        }
+    }
+}
+
+impl Sqe<SpliceData> {
+    /// Creates a new `Sqe` for `splice(2)`.
+    pub fn splice(
+        fd_in: RawFd,
+        off_in: Option<u64>,
+        fd_out: RawFd,
+        off_out: Option<u64>,
+        len: u32,
+        flags: u32,
+    ) -> Sqe<SpliceData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SpliceData {
+                fd_in,
+                off_in,
+                fd_out,
+                off_out,
+                len,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<TeeData> {
+    /// Creates a new `Sqe` for `tee(2)`.
+    pub fn tee(fd_in: RawFd, fd_out: RawFd, len: u32, flags: u32) -> Sqe<TeeData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: TeeData {
+                fd_in,
+                fd_out,
+                len,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<MsgRingData> {
+    /// Creates a new `Sqe` for posting a completion with `result` and `user_data` on
+    /// the ring of `target_ring_fd`.
+    pub fn msg_ring(target_ring_fd: RawFd, result: i32, user_data: u64) -> Sqe<MsgRingData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: MsgRingData {
+                target_ring_fd,
+                result,
+                user_data,
+            },
+        }
+    }
+}
+
+impl Sqe<EpollCtlData> {
+    /// Creates a new `Sqe` for `epoll_ctl(2)`.
+    ///
+    /// `op` is one of `EPOLL_CTL_ADD`, `EPOLL_CTL_MOD` and `EPOLL_CTL_DEL`.
+    pub fn epoll_ctl(
+        epfd: RawFd,
+        op: i32,
+        fd: RawFd,
+        event: libc::epoll_event,
+    ) -> Sqe<EpollCtlData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: EpollCtlData {
+                epfd,
+                op,
+                fd,
+                event: Box::new(event),
+            },
+        }
+    }
+}
+
+impl Sqe<WaitidData> {
+    /// Creates a new `Sqe` for `waitid(2)`.
+    pub fn waitid(idtype: libc::idtype_t, id: libc::id_t, options: i32) -> Sqe<WaitidData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: WaitidData {
+                idtype,
+                id,
+                options,
+                siginfo: Box::new(unsafe { mem::zeroed() }),
+            },
+        }
+    }
+}
+
+impl Sqe<SetxattrData> {
+    /// Creates a new `Sqe` for `setxattr(2)` setting the attribute `name` of `path` to
+    /// the contents of `buf`.
+    pub fn setxattr(path: CString, name: CString, buf: UringBuf, flags: i32) -> Sqe<SetxattrData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SetxattrData {
+                path,
+                name,
+                buf,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<GetxattrData> {
+    /// Creates a new `Sqe` for `getxattr(2)` reading the attribute `name` of `path`
+    /// into `buf`.
+    pub fn getxattr(path: CString, name: CString, buf: UringBuf) -> Sqe<GetxattrData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: GetxattrData { path, name, buf },
+        }
+    }
+}
+
+impl Sqe<SyncFileRangeData> {
+    /// Creates a new `Sqe` for `sync_file_range(2)`.
+    pub fn sync_file_range(
+        fd: RawFd,
+        offset: u64,
+        nbytes: u64,
+        flags: u32,
+    ) -> Sqe<SyncFileRangeData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SyncFileRangeData {
+                fd,
+                offset,
+                nbytes,
+                flags,
+            },
+        }
+    }
+}
+
+impl Sqe<SendZcData> {
+    /// Creates a new `Sqe` for zero-copy `send(2)` sending `buf`.
+    ///
+    /// The kernel posts a notification after the result once it no longer uses
+    /// the buffer; the buffer is returned only after the notification.
+    pub fn send_zc(fd: RawFd, buf: UringBuf, flags: i32, zc_flags: u16) -> Sqe<SendZcData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: SendZcData {
+                fd,
+                buf,
+                flags,
+                zc_flags,
+            },
+        }
+    }
+}
+
+impl Sqe<OpenatData> {
+    /// Creates a new `Sqe` for `openat(2)`.
+    pub fn openat(dirfd: RawFd, path: CString, flags: i32, mode: u32) -> Sqe<OpenatData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: OpenatData {
+                dirfd,
+                path,
+                flags,
+                mode,
+                direct: false,
+            },
+        }
+    }
+
+    /// Creates a new `Sqe` for `openat(2)` installing the file in a free slot of the
+    /// registered file table instead of the fd table.
+    ///
+    /// The result is the index of the slot, to be used with [`Sqe::fixed_file`].
+    pub fn openat_direct(dirfd: RawFd, path: CString, flags: i32, mode: u32) -> Sqe<OpenatData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: OpenatData {
+                dirfd,
+                path,
+                flags,
+                mode,
+                direct: true,
+            },
+        }
+    }
+}
+
+impl Sqe<BindData> {
+    /// Creates a new `Sqe` for `bind(2)`.
+    pub fn bind(fd: RawFd, addr: SocketAddr) -> Sqe<BindData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: BindData {
+                fd,
+                addr,
+                raw: Box::new(unsafe { mem::zeroed() }),
+            },
+        }
+    }
+}
+
+impl Sqe<ListenData> {
+    /// Creates a new `Sqe` for `listen(2)`.
+    pub fn listen(fd: RawFd, backlog: i32) -> Sqe<ListenData> {
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data: ListenData { fd, backlog },
+        }
+    }
+}
+
 impl<T: UringData> Sqe<T> {
     /// Creates a new `Sqe`.
     pub fn new(data: T) -> Sqe<T> {
-        Sqe { flag: 0, data }
+        Sqe {
+            flag: 0,
+            fd_borrowed: false,
+            tag: None,
+            data,
+        }
     }
 
     /// Enables drain.
@@ -101,25 +755,54 @@ impl<T: UringData> Sqe<T> {
         self.flag |= IOSQE_CQE_SKIP_SUCCESS;
         self
     }
+
+    /// Uses the fd of the operation as an index into the registered file table, e.g.
+    /// one allocated by [`Sqe::openat_direct`].
+    pub fn fixed_file(mut self) -> Sqe<T> {
+        self.flag |= IOSQE_FIXED_FILE;
+        self
+    }
+
+    /// Attaches a tag to the operation, returned by
+    /// [`IoResult::tag`](crate::result::IoResult::tag) of its result.
+    ///
+    /// Unlike the [`OperationId`](crate::OperationId), the tag is chosen by the caller
+    /// and need not be unique.
+    pub fn tag(mut self, tag: u64) -> Sqe<T> {
+        self.tag = Some(tag);
+        self
+    }
 }
 
 /// Input for asynchronous `read(2)`.
-pub struct ReadData {
+pub struct ReadData<'a> {
     pub fd: RawFd,
-    pub buf: UringBuf,
+    pub buf: UringReadBuf<'a>,
+    /// `u64::MAX` reads at the current file position, see [`Offset::Current`].
     pub offset: u64,
 }
-impl UringData for ReadData {}
+impl<'a> UringData for ReadData<'a> {}
 
-impl Into<UringOperationKind> for Sqe<ReadData> {
+impl<'a> Into<UringOperationKind> for Sqe<ReadData<'a>> {
     fn into(self) -> UringOperationKind {
-        UringOperationKind::Read(self.data)
+        let ReadData { fd, buf, offset } = self.data;
+        // The handle holds the borrow of a slice until the entry is removed.
+        UringOperationKind::Read(ReadData {
+            fd,
+            buf: unsafe { buf.into_static() },
+            offset,
+        })
     }
 }
 
-impl<'a> UringSqe<'a> for Sqe<ReadData> {
+impl<'a> UringSqe<'a> for Sqe<ReadData<'a>> {
     type Handle = ReadHandle<'a>;
 
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        self.data.buf.validate()
+    }
+
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
         unsafe {
             io_uring_prep_read(
@@ -131,35 +814,55 @@ impl<'a> UringSqe<'a> for Sqe<ReadData> {
             );
         }
     }
+
+    fn borrows(&self) -> bool {
+        self.data.buf.is_borrowed()
+    }
 }
 
 /// Input for asynchronous `write(2)`.
-pub struct WriteData {
+pub struct WriteData<'a> {
     pub fd: RawFd,
-    pub buf: UringBuf,
+    pub buf: UringWriteBuf<'a>,
+    /// `u64::MAX` writes at the current file position, see [`Offset::Current`].
     pub offset: u64,
 }
-impl UringData for WriteData {}
+impl<'a> UringData for WriteData<'a> {}
 
-impl Into<UringOperationKind> for Sqe<WriteData> {
+impl<'a> Into<UringOperationKind> for Sqe<WriteData<'a>> {
     fn into(self) -> UringOperationKind {
-        UringOperationKind::Write(self.data)
+        let WriteData { fd, buf, offset } = self.data;
+        // The handle holds the borrow of a slice until the entry is removed.
+        UringOperationKind::Write(WriteData {
+            fd,
+            buf: unsafe { buf.into_static() },
+            offset,
+        })
     }
 }
 
-impl<'a> UringSqe<'a> for Sqe<WriteData> {
+impl<'a> UringSqe<'a> for Sqe<WriteData<'a>> {
     type Handle = WriteHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        self.data.buf.validate()
+    }
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
         unsafe {
             io_uring_prep_write(
                 sqe.as_ptr(),
                 self.data.fd,
-                self.data.buf.as_mut_ptr() as *mut _,
+                self.data.buf.as_ptr() as *mut _,
                 self.data.buf.len() as u32,
                 self.data.offset,
             );
         }
     }
+
+    fn borrows(&self) -> bool {
+        self.data.buf.is_borrowed()
+    }
 }
 
 /// Input for asynchronous `fsync(2)`.
@@ -177,6 +880,10 @@ impl Into<UringOperationKind> for Sqe<FsyncData> {
 impl<'a> UringSqe<'a> for Sqe<FsyncData> {
     type Handle = FsyncHandle<'a>;
 
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
         unsafe {
             io_uring_prep_fsync(sqe.as_ptr(), self.data.fd, 0);
@@ -199,6 +906,10 @@ impl Into<UringOperationKind> for Sqe<FdatasyncData> {
 impl<'a> UringSqe<'a> for Sqe<FdatasyncData> {
     type Handle = FdatasyncHandle<'a>;
 
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
         unsafe {
             io_uring_prep_fsync(sqe.as_ptr(), self.data.fd, IORING_FSYNC_DATASYNC);
@@ -208,7 +919,7 @@ impl<'a> UringSqe<'a> for Sqe<FdatasyncData> {
 
 /// Input for asynchronous `madvise(2)`.
 pub struct MadviseData {
-    pub buf: UringBuf,
+    pub buf: UringAdviseBuf,
     pub advise: Madvise,
 }
 impl UringData for MadviseData {}
@@ -222,11 +933,15 @@ impl Into<UringOperationKind> for Sqe<MadviseData> {
 impl<'a> UringSqe<'a> for Sqe<MadviseData> {
     type Handle = MadviseHandle<'a>;
 
+    fn validate(&self) -> Result<()> {
+        self.data.buf.validate()
+    }
+
     fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
         unsafe {
             io_uring_prep_madvise(
                 sqe.as_ptr(),
-                self.data.buf.as_mut_ptr() as *mut _,
+                self.data.buf.as_ptr() as *mut _,
                 self.data.buf.len() as i64,
                 self.data.advise as i32,
             );
@@ -234,26 +949,1201 @@ impl<'a> UringSqe<'a> for Sqe<MadviseData> {
     }
 }
 
-/// The advise to `madvise(2)`.
-// FIXME: add more variants.
-#[repr(i32)]
-#[derive(Debug, Copy, Clone)]
-pub enum Madvise {
-    Normal = libc::MADV_NORMAL,
-    DontNeed = libc::MADV_DONTNEED,
+/// Input for asynchronous `posix_fadvise(2)`.
+pub struct FadviseData {
+    pub fd: RawFd,
+    pub offset: u64,
+    pub len: u64,
+    /// One of `POSIX_FADV_*` constants, e.g. [`libc::POSIX_FADV_WILLNEED`].
+    pub advice: i32,
 }
+impl UringData for FadviseData {}
 
-pub(crate) enum UringOperationKind {
-    /// Asynchronous `read(2)`.
-    ///
-    /// Equivalent to `io_uring_prep_read`.
-    Read(ReadData),
-    /// Asynchronous `write(2).
-    ///
-    /// Equivalent to `io_uring_prep_write`
-    Write(WriteData),
-    /// Asynchronous `fsync(2)`.
-    ///
+impl Into<UringOperationKind> for Sqe<FadviseData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Fadvise(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<FadviseData> {
+    type Handle = FadviseHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_fadvise(
+                sqe.as_ptr(),
+                self.data.fd,
+                self.data.offset,
+                self.data.len as _,
+                self.data.advice,
+            );
+        }
+    }
+}
+
+// Since Linux 5.4; not defined by all versions of the libc crate.
+const MADV_COLD: i32 = 20;
+const MADV_PAGEOUT: i32 = 21;
+
+/// The advise to `madvise(2)`.
+#[repr(i32)]
+#[derive(Debug, Copy, Clone)]
+pub enum Madvise {
+    Normal = libc::MADV_NORMAL,
+    Random = libc::MADV_RANDOM,
+    Sequential = libc::MADV_SEQUENTIAL,
+    WillNeed = libc::MADV_WILLNEED,
+    DontNeed = libc::MADV_DONTNEED,
+    Free = libc::MADV_FREE,
+    Remove = libc::MADV_REMOVE,
+    HugePage = libc::MADV_HUGEPAGE,
+    NoHugePage = libc::MADV_NOHUGEPAGE,
+    /// Deactivates the pages so that they are reclaimed first under memory pressure.
+    Cold = MADV_COLD,
+    /// Reclaims the pages right away.
+    PageOut = MADV_PAGEOUT,
+}
+
+/// Input for asynchronous `ftruncate(2)`.
+pub struct FtruncateData {
+    pub fd: RawFd,
+    pub len: u64,
+}
+impl UringData for FtruncateData {}
+
+impl Into<UringOperationKind> for Sqe<FtruncateData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Ftruncate(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<FtruncateData> {
+    type Handle = FtruncateHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_ftruncate(sqe.as_ptr(), self.data.fd, self.data.len as _);
+        }
+    }
+}
+
+/// Input for asynchronous multishot `accept4(2)`.
+///
+/// The SQE stays armed and posts a completion for every accepted connection until
+/// it fails or is cancelled.
+pub struct MultishotAcceptData {
+    pub fd: RawFd,
+    /// Flags to `accept4(2)`, e.g. [`libc::SOCK_CLOEXEC`].
+    pub flags: i32,
+}
+impl UringData for MultishotAcceptData {}
+
+impl Into<UringOperationKind> for Sqe<MultishotAcceptData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::MultishotAccept(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<MultishotAcceptData> {
+    type Handle = MultishotAcceptHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_multishot_accept(
+                sqe.as_ptr(),
+                self.data.fd,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                self.data.flags,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `shutdown(2)`.
+pub struct ShutdownData {
+    pub fd: RawFd,
+    /// One of [`libc::SHUT_RD`], [`libc::SHUT_WR`] or [`libc::SHUT_RDWR`].
+    pub how: i32,
+}
+impl UringData for ShutdownData {}
+
+impl Into<UringOperationKind> for Sqe<ShutdownData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Shutdown(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<ShutdownData> {
+    type Handle = ShutdownHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_shutdown(sqe.as_ptr(), self.data.fd, self.data.how);
+        }
+    }
+}
+
+/// Input for asynchronous `socket(2)`.
+pub struct SocketData {
+    pub domain: i32,
+    pub type_: i32,
+    pub protocol: i32,
+    /// Currently unused by the kernel; must be 0.
+    pub flags: u32,
+    /// Let the kernel allocate a slot in the registered file table
+    /// (`IORING_FILE_INDEX_ALLOC`) and return its index instead of an fd.
+    pub direct: bool,
+}
+impl UringData for SocketData {}
+
+impl Into<UringOperationKind> for Sqe<SocketData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Socket(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SocketData> {
+    type Handle = SocketHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            if self.data.direct {
+                io_uring_prep_socket_direct_alloc(
+                    sqe.as_ptr(),
+                    self.data.domain,
+                    self.data.type_,
+                    self.data.protocol,
+                    self.data.flags,
+                );
+            } else {
+                io_uring_prep_socket(
+                    sqe.as_ptr(),
+                    self.data.domain,
+                    self.data.type_,
+                    self.data.protocol,
+                    self.data.flags,
+                );
+            }
+        }
+    }
+}
+
+/// Input for asynchronous `close(2)`.
+pub struct CloseData {
+    pub fd: RawFd,
+}
+impl UringData for CloseData {}
+
+impl Into<UringOperationKind> for Sqe<CloseData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Close(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<CloseData> {
+    type Handle = CloseHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_close(sqe.as_ptr(), self.data.fd);
+        }
+    }
+}
+
+/// Input for asynchronous `recv(2)` with buffer selection.
+///
+/// The kernel picks a buffer from `buf_ring` when data arrives, and the result
+/// reports which one was used.
+pub struct RecvData<'a> {
+    pub fd: RawFd,
+    pub buf_ring: &'a BufRing<'a>,
+    /// Flags to `recv(2)`, e.g. [`libc::MSG_WAITALL`].
+    pub flags: i32,
+}
+impl<'a> UringData for RecvData<'a> {}
+
+impl<'a> Into<UringOperationKind> for Sqe<RecvData<'a>> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Recv {
+            bgid: self.data.buf_ring.bgid(),
+        }
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<RecvData<'a>> {
+    type Handle = RecvHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        self.flag |= IOSQE_BUFFER_SELECT;
+        unsafe {
+            io_uring_prep_recv(
+                sqe.as_ptr(),
+                self.data.fd,
+                ptr::null_mut(),
+                0,
+                self.data.flags,
+            );
+            set_buf_group(sqe, self.data.buf_ring.bgid());
+        }
+    }
+
+    fn borrows(&self) -> bool {
+        true
+    }
+}
+
+/// Input for asynchronous multishot `recv(2)` with buffer selection.
+///
+/// The SQE stays armed and posts a completion for every received chunk of data until
+/// it fails, e.g. with `ENOBUFS` once `buf_ring` runs out of buffers, the peer closes
+/// the connection or it is cancelled.
+pub struct MultishotRecvData<'a> {
+    pub fd: RawFd,
+    pub buf_ring: &'a BufRing<'a>,
+    /// Flags to `recv(2)`.
+    pub flags: i32,
+}
+impl<'a> UringData for MultishotRecvData<'a> {}
+
+impl<'a> Into<UringOperationKind> for Sqe<MultishotRecvData<'a>> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::MultishotRecv {
+            bgid: self.data.buf_ring.bgid(),
+        }
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<MultishotRecvData<'a>> {
+    type Handle = MultishotRecvHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        self.flag |= IOSQE_BUFFER_SELECT;
+        unsafe {
+            io_uring_prep_recv_multishot(
+                sqe.as_ptr(),
+                self.data.fd,
+                ptr::null_mut(),
+                0,
+                self.data.flags,
+            );
+            set_buf_group(sqe, self.data.buf_ring.bgid());
+        }
+    }
+
+    fn borrows(&self) -> bool {
+        true
+    }
+}
+
+fn check_fd(fd: RawFd) -> Result<()> {
+    if fd < 0 {
+        Err(Error::InvalidSqe("negative fd"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Sets the buffer group of `sqe`, for which liburing has no helper.
+unsafe fn set_buf_group(sqe: NonNull<io_uring_sqe>, bgid: u16) {
+    // `buf_group` shares a union with `buf_index`.
+    (*sqe.as_ptr()).__bindgen_anon_4.buf_group = bgid;
+}
+
+/// `msghdr` of `sendmsg(2)` and `recvmsg(2)` together with the memory it points to.
+///
+/// Kept in a `Box` so that the pointers handed to the kernel stay valid while the
+/// operation entry moves.
+pub(crate) struct MsgHdr {
+    hdr: libc::msghdr,
+    iov: libc::iovec,
+    buf: UringBuf,
+    /// Ancillary data, aligned for `cmsghdr`.
+    control: Vec<u64>,
+}
+
+impl MsgHdr {
+    fn new(buf: UringBuf, recv: bool, control_len: usize) -> Box<MsgHdr> {
+        let mut msg = Box::new(MsgHdr {
+            hdr: unsafe { mem::zeroed() },
+            iov: libc::iovec {
+                iov_base: ptr::null_mut(),
+                iov_len: 0,
+            },
+            buf,
+            control: vec![0; control_len.div_ceil(8)],
+        });
+        msg.iov = if recv {
+            libc::iovec {
+                iov_base: msg.buf.spare_ptr() as *mut _,
+                iov_len: msg.buf.spare_len(),
+            }
+        } else {
+            libc::iovec {
+                iov_base: msg.buf.as_ptr() as *mut _,
+                iov_len: msg.buf.len(),
+            }
+        };
+        msg.hdr.msg_iov = ptr::addr_of_mut!(msg.iov);
+        msg.hdr.msg_iovlen = 1;
+        if control_len > 0 {
+            msg.hdr.msg_control = msg.control.as_mut_ptr() as *mut _;
+            msg.hdr.msg_controllen = control_len as _;
+        }
+        msg
+    }
+
+    fn control_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.control.as_mut_ptr() as *mut u8,
+                self.hdr.msg_controllen as usize,
+            )
+        }
+    }
+
+    /// Returns the ancillary data; after `recvmsg(2)`, only what the kernel filled.
+    pub(crate) fn control(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                self.control.as_ptr() as *const u8,
+                self.hdr.msg_controllen as usize,
+            )
+        }
+    }
+
+    /// Returns the file descriptors in the `SCM_RIGHTS` messages of the ancillary data.
+    pub(crate) fn fds(&self) -> Vec<RawFd> {
+        let mut fds = Vec::new();
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&self.hdr);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(cmsg) as *const RawFd;
+                    let len = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                    for i in 0..len / mem::size_of::<RawFd>() {
+                        fds.push(ptr::read_unaligned(data.add(i)));
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&self.hdr, cmsg);
+            }
+        }
+        fds
+    }
+
+    pub(crate) fn into_buf(self) -> UringBuf {
+        self.buf
+    }
+}
+
+/// Builds ancillary data passing `fds` in an `SCM_RIGHTS` message, for
+/// [`Sqe::sendmsg`].
+pub fn scm_rights(fds: &[RawFd]) -> Vec<u8> {
+    let data_len = mem::size_of_val(fds) as u32;
+    let space = unsafe { libc::CMSG_SPACE(data_len) } as usize;
+    let mut control = vec![0u64; space.div_ceil(8)];
+    unsafe {
+        let mut hdr: libc::msghdr = mem::zeroed();
+        hdr.msg_control = control.as_mut_ptr() as *mut _;
+        hdr.msg_controllen = space as _;
+        let cmsg = libc::CMSG_FIRSTHDR(&hdr);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(data_len) as _;
+        ptr::copy_nonoverlapping(fds.as_ptr(), libc::CMSG_DATA(cmsg) as *mut RawFd, fds.len());
+        slice::from_raw_parts(control.as_ptr() as *const u8, space).to_vec()
+    }
+}
+
+/// Input for asynchronous `sendmsg(2)`.
+pub struct SendmsgData {
+    pub fd: RawFd,
+    /// Flags to `sendmsg(2)`, e.g. [`libc::MSG_NOSIGNAL`].
+    pub flags: i32,
+    pub(crate) msg: Box<MsgHdr>,
+}
+impl UringData for SendmsgData {}
+
+impl Into<UringOperationKind> for Sqe<SendmsgData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Sendmsg(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SendmsgData> {
+    type Handle = SendmsgHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        self.data.msg.buf.validate()
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_sendmsg(
+                sqe.as_ptr(),
+                self.data.fd,
+                ptr::addr_of!(self.data.msg.hdr).cast(),
+                self.data.flags as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `recvmsg(2)`.
+pub struct RecvmsgData {
+    pub fd: RawFd,
+    /// Flags to `recvmsg(2)`, e.g. [`libc::MSG_CMSG_CLOEXEC`].
+    pub flags: i32,
+    pub(crate) msg: Box<MsgHdr>,
+}
+impl UringData for RecvmsgData {}
+
+impl Into<UringOperationKind> for Sqe<RecvmsgData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Recvmsg(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<RecvmsgData> {
+    type Handle = RecvmsgHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        self.data.msg.buf.validate()
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_recvmsg(
+                sqe.as_ptr(),
+                self.data.fd,
+                ptr::addr_of_mut!(self.data.msg.hdr).cast(),
+                self.data.flags as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `renameat2(2)`.
+pub struct RenameData {
+    /// Directory `oldpath` is relative to, or [`libc::AT_FDCWD`].
+    pub olddirfd: RawFd,
+    pub oldpath: CString,
+    /// Directory `newpath` is relative to, or [`libc::AT_FDCWD`].
+    pub newdirfd: RawFd,
+    pub newpath: CString,
+    /// [`libc::RENAME_NOREPLACE`], [`libc::RENAME_EXCHANGE`] or 0.
+    pub flags: u32,
+}
+impl UringData for RenameData {}
+
+impl Into<UringOperationKind> for Sqe<RenameData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Rename(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<RenameData> {
+    type Handle = RenameHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_renameat(
+                sqe.as_ptr(),
+                self.data.olddirfd,
+                self.data.oldpath.as_ptr(),
+                self.data.newdirfd,
+                self.data.newpath.as_ptr(),
+                self.data.flags as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `unlinkat(2)`.
+pub struct UnlinkData {
+    /// Directory `path` is relative to, or [`libc::AT_FDCWD`].
+    pub dirfd: RawFd,
+    pub path: CString,
+    /// [`libc::AT_REMOVEDIR`] to remove a directory, or 0.
+    pub flags: i32,
+}
+impl UringData for UnlinkData {}
+
+impl Into<UringOperationKind> for Sqe<UnlinkData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Unlink(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<UnlinkData> {
+    type Handle = UnlinkHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_unlinkat(
+                sqe.as_ptr(),
+                self.data.dirfd,
+                self.data.path.as_ptr(),
+                self.data.flags,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `mkdirat(2)`.
+pub struct MkdirData {
+    /// Directory `path` is relative to, or [`libc::AT_FDCWD`].
+    pub dirfd: RawFd,
+    pub path: CString,
+    pub mode: u32,
+}
+impl UringData for MkdirData {}
+
+impl Into<UringOperationKind> for Sqe<MkdirData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Mkdir(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<MkdirData> {
+    type Handle = MkdirHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_mkdirat(
+                sqe.as_ptr(),
+                self.data.dirfd,
+                self.data.path.as_ptr(),
+                self.data.mode as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `symlinkat(2)`.
+pub struct SymlinkData {
+    pub target: CString,
+    /// Directory `linkpath` is relative to, or [`libc::AT_FDCWD`].
+    pub newdirfd: RawFd,
+    pub linkpath: CString,
+}
+impl UringData for SymlinkData {}
+
+impl Into<UringOperationKind> for Sqe<SymlinkData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Symlink(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SymlinkData> {
+    type Handle = SymlinkHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_symlinkat(
+                sqe.as_ptr(),
+                self.data.target.as_ptr(),
+                self.data.newdirfd,
+                self.data.linkpath.as_ptr(),
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `linkat(2)`.
+pub struct LinkData {
+    /// Directory `oldpath` is relative to, or [`libc::AT_FDCWD`].
+    pub olddirfd: RawFd,
+    pub oldpath: CString,
+    /// Directory `newpath` is relative to, or [`libc::AT_FDCWD`].
+    pub newdirfd: RawFd,
+    pub newpath: CString,
+    /// [`libc::AT_SYMLINK_FOLLOW`] or 0.
+    pub flags: i32,
+}
+impl UringData for LinkData {}
+
+impl Into<UringOperationKind> for Sqe<LinkData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Link(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<LinkData> {
+    type Handle = LinkHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_linkat(
+                sqe.as_ptr(),
+                self.data.olddirfd,
+                self.data.oldpath.as_ptr(),
+                self.data.newdirfd,
+                self.data.newpath.as_ptr(),
+                self.data.flags,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous no-op.
+///
+/// Useful to measure the submission and completion overhead, or as a fence with
+/// [`drain`](Sqe::drain) or [`link`](Sqe::link).
+pub struct NopData {}
+impl UringData for NopData {}
+
+impl Into<UringOperationKind> for Sqe<NopData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Nop(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<NopData> {
+    type Handle = NopHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_nop(sqe.as_ptr());
+        }
+    }
+}
+
+/// Input for asynchronous `splice(2)`.
+///
+/// One of `fd_in` and `fd_out` must be a pipe.
+pub struct SpliceData {
+    pub fd_in: RawFd,
+    /// Offset to read from, or `None` for the current position. Must be `None` for a pipe.
+    pub off_in: Option<u64>,
+    pub fd_out: RawFd,
+    /// Offset to write to, or `None` for the current position. Must be `None` for a pipe.
+    pub off_out: Option<u64>,
+    pub len: u32,
+    /// `SPLICE_F_*` flags, e.g. [`libc::SPLICE_F_MOVE`].
+    pub flags: u32,
+}
+impl UringData for SpliceData {}
+
+impl Into<UringOperationKind> for Sqe<SpliceData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Splice(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SpliceData> {
+    type Handle = SpliceHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd_in)?;
+        check_fd(self.data.fd_out)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_splice(
+                sqe.as_ptr(),
+                self.data.fd_in,
+                splice_offset(self.data.off_in),
+                self.data.fd_out,
+                splice_offset(self.data.off_out),
+                self.data.len,
+                self.data.flags,
+            );
+        }
+    }
+}
+
+/// Converts an offset of `splice(2)` to the form liburing expects, -1 meaning the
+/// current position.
+fn splice_offset(offset: Option<u64>) -> i64 {
+    offset.map_or(-1, |offset| offset as i64)
+}
+
+/// Input for asynchronous `tee(2)`.
+///
+/// Both `fd_in` and `fd_out` must be pipes.
+pub struct TeeData {
+    pub fd_in: RawFd,
+    pub fd_out: RawFd,
+    pub len: u32,
+    /// `SPLICE_F_*` flags, e.g. [`libc::SPLICE_F_NONBLOCK`].
+    pub flags: u32,
+}
+impl UringData for TeeData {}
+
+impl Into<UringOperationKind> for Sqe<TeeData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Tee(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<TeeData> {
+    type Handle = TeeHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd_in)?;
+        check_fd(self.data.fd_out)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_tee(
+                sqe.as_ptr(),
+                self.data.fd_in,
+                self.data.fd_out,
+                self.data.len,
+                self.data.flags,
+            );
+        }
+    }
+}
+
+/// Input for posting a completion on another ring.
+pub struct MsgRingData {
+    pub target_ring_fd: RawFd,
+    /// `res` of the completion posted on the target ring.
+    pub result: i32,
+    /// `user_data` of the completion posted on the target ring.
+    pub user_data: u64,
+}
+impl UringData for MsgRingData {}
+
+impl Into<UringOperationKind> for Sqe<MsgRingData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::MsgRing(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<MsgRingData> {
+    type Handle = MsgRingHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.target_ring_fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_msg_ring(
+                sqe.as_ptr(),
+                self.data.target_ring_fd,
+                self.data.result as u32,
+                self.data.user_data,
+                0,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `epoll_ctl(2)`.
+pub struct EpollCtlData {
+    pub epfd: RawFd,
+    pub op: i32,
+    pub fd: RawFd,
+    /// Boxed so that it stays at the same address until the kernel reads it on submission.
+    pub(crate) event: Box<libc::epoll_event>,
+}
+impl UringData for EpollCtlData {}
+
+impl Into<UringOperationKind> for Sqe<EpollCtlData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::EpollCtl(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<EpollCtlData> {
+    type Handle = EpollCtlHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.epfd)?;
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_epoll_ctl(
+                sqe.as_ptr(),
+                self.data.epfd,
+                self.data.fd,
+                self.data.op,
+                &mut *self.data.event as *mut libc::epoll_event as *mut _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `waitid(2)`.
+pub struct WaitidData {
+    pub idtype: libc::idtype_t,
+    pub id: libc::id_t,
+    pub options: i32,
+    /// Boxed so that it stays at the same address until the kernel fills it.
+    pub(crate) siginfo: Box<libc::siginfo_t>,
+}
+impl UringData for WaitidData {}
+
+impl Into<UringOperationKind> for Sqe<WaitidData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Waitid(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<WaitidData> {
+    type Handle = WaitidHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_waitid(
+                sqe.as_ptr(),
+                self.data.idtype as _,
+                self.data.id as _,
+                &mut *self.data.siginfo as *mut libc::siginfo_t as *mut _,
+                self.data.options,
+                0,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `setxattr(2)`.
+pub struct SetxattrData {
+    pub path: CString,
+    pub name: CString,
+    /// Value of the attribute.
+    pub buf: UringBuf,
+    /// [`libc::XATTR_CREATE`], [`libc::XATTR_REPLACE`] or 0.
+    pub flags: i32,
+}
+impl UringData for SetxattrData {}
+
+impl Into<UringOperationKind> for Sqe<SetxattrData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Setxattr(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SetxattrData> {
+    type Handle = SetxattrHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        self.data.buf.validate()
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_setxattr(
+                sqe.as_ptr(),
+                self.data.name.as_ptr(),
+                self.data.buf.as_ptr() as *const _,
+                self.data.path.as_ptr(),
+                self.data.flags,
+                self.data.buf.len() as u32,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `getxattr(2)`.
+pub struct GetxattrData {
+    pub path: CString,
+    pub name: CString,
+    /// Buffer for the value of the attribute.
+    pub buf: UringBuf,
+}
+impl UringData for GetxattrData {}
+
+impl Into<UringOperationKind> for Sqe<GetxattrData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Getxattr(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<GetxattrData> {
+    type Handle = GetxattrHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        self.data.buf.validate()
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_getxattr(
+                sqe.as_ptr(),
+                self.data.name.as_ptr(),
+                self.data.buf.spare_ptr() as *mut _,
+                self.data.path.as_ptr(),
+                self.data.buf.spare_len() as u32,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `sync_file_range(2)`.
+pub struct SyncFileRangeData {
+    pub fd: RawFd,
+    pub offset: u64,
+    /// Number of bytes to sync from `offset`, or 0 for up to the end of the file.
+    pub nbytes: u64,
+    /// Combination of [`libc::SYNC_FILE_RANGE_WAIT_BEFORE`],
+    /// [`libc::SYNC_FILE_RANGE_WRITE`] and [`libc::SYNC_FILE_RANGE_WAIT_AFTER`].
+    pub flags: u32,
+}
+impl UringData for SyncFileRangeData {}
+
+impl Into<UringOperationKind> for Sqe<SyncFileRangeData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::SyncFileRange(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SyncFileRangeData> {
+    type Handle = SyncFileRangeHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        if self.data.nbytes > u32::MAX as u64 {
+            return Err(Error::InvalidSqe("sync_file_range longer than u32::MAX"));
+        }
+        Ok(())
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_sync_file_range(
+                sqe.as_ptr(),
+                self.data.fd,
+                self.data.nbytes as _,
+                self.data.offset,
+                self.data.flags as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous zero-copy `send(2)`.
+pub struct SendZcData {
+    pub fd: RawFd,
+    pub buf: UringBuf,
+    /// Flags to `send(2)`, e.g. [`libc::MSG_NOSIGNAL`].
+    pub flags: i32,
+    /// Flags of the zero-copy send, e.g. `IORING_RECVSEND_FIXED_BUF`.
+    pub zc_flags: u16,
+}
+impl UringData for SendZcData {}
+
+impl Into<UringOperationKind> for Sqe<SendZcData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::SendZc(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<SendZcData> {
+    type Handle = SendZcHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)?;
+        self.data.buf.validate()
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_send_zc(
+                sqe.as_ptr(),
+                self.data.fd,
+                self.data.buf.as_ptr() as *const _,
+                self.data.buf.len() as _,
+                self.data.flags,
+                self.data.zc_flags as _,
+            );
+        }
+    }
+}
+
+/// Input for asynchronous `openat(2)`.
+pub struct OpenatData {
+    pub dirfd: RawFd,
+    pub path: CString,
+    pub flags: i32,
+    pub mode: u32,
+    /// Let the kernel allocate a slot in the registered file table
+    /// (`IORING_FILE_INDEX_ALLOC`) and return its index instead of an fd.
+    pub direct: bool,
+}
+impl UringData for OpenatData {}
+
+impl Into<UringOperationKind> for Sqe<OpenatData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Openat(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<OpenatData> {
+    type Handle = OpenatHandle<'a>;
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            if self.data.direct {
+                io_uring_prep_openat_direct(
+                    sqe.as_ptr(),
+                    self.data.dirfd,
+                    self.data.path.as_ptr(),
+                    self.data.flags,
+                    self.data.mode as _,
+                    IORING_FILE_INDEX_ALLOC as _,
+                );
+            } else {
+                io_uring_prep_openat(
+                    sqe.as_ptr(),
+                    self.data.dirfd,
+                    self.data.path.as_ptr(),
+                    self.data.flags,
+                    self.data.mode as _,
+                );
+            }
+        }
+    }
+}
+
+/// Input for asynchronous `bind(2)`.
+pub struct BindData {
+    pub fd: RawFd,
+    pub addr: SocketAddr,
+    /// `addr` as passed to the kernel; boxed so that it stays at the same address until
+    /// the kernel reads it on submission.
+    pub(crate) raw: Box<libc::sockaddr_storage>,
+}
+impl UringData for BindData {}
+
+impl Into<UringOperationKind> for Sqe<BindData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Bind(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<BindData> {
+    type Handle = BindHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        let len = write_sockaddr(&self.data.addr, &mut self.data.raw);
+        unsafe {
+            io_uring_prep_bind(
+                sqe.as_ptr(),
+                self.data.fd,
+                (&mut *self.data.raw as *mut libc::sockaddr_storage).cast(),
+                len,
+            );
+        }
+    }
+}
+
+/// Writes `addr` into `storage` and returns its length.
+fn write_sockaddr(addr: &SocketAddr, storage: &mut libc::sockaddr_storage) -> libc::socklen_t {
+    let storage = storage as *mut libc::sockaddr_storage;
+    match addr {
+        SocketAddr::V4(addr) => unsafe {
+            ptr::write(
+                storage.cast(),
+                libc::sockaddr_in {
+                    sin_family: libc::AF_INET as libc::sa_family_t,
+                    sin_port: addr.port().to_be(),
+                    sin_addr: libc::in_addr {
+                        s_addr: u32::from_ne_bytes(addr.ip().octets()),
+                    },
+                    sin_zero: [0; 8],
+                },
+            );
+            mem::size_of::<libc::sockaddr_in>() as libc::socklen_t
+        },
+        SocketAddr::V6(addr) => unsafe {
+            ptr::write(
+                storage.cast(),
+                libc::sockaddr_in6 {
+                    sin6_family: libc::AF_INET6 as libc::sa_family_t,
+                    sin6_port: addr.port().to_be(),
+                    sin6_flowinfo: addr.flowinfo(),
+                    sin6_addr: libc::in6_addr {
+                        s6_addr: addr.ip().octets(),
+                    },
+                    sin6_scope_id: addr.scope_id(),
+                },
+            );
+            mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t
+        },
+    }
+}
+
+/// Input for asynchronous `listen(2)`.
+pub struct ListenData {
+    pub fd: RawFd,
+    pub backlog: i32,
+}
+impl UringData for ListenData {}
+
+impl Into<UringOperationKind> for Sqe<ListenData> {
+    fn into(self) -> UringOperationKind {
+        UringOperationKind::Listen(self.data)
+    }
+}
+
+impl<'a> UringSqe<'a> for Sqe<ListenData> {
+    type Handle = ListenHandle<'a>;
+
+    fn validate(&self) -> Result<()> {
+        check_fd(self.data.fd)
+    }
+
+    fn prepare(&mut self, sqe: NonNull<io_uring_sqe>) {
+        unsafe {
+            io_uring_prep_listen(sqe.as_ptr(), self.data.fd, self.data.backlog);
+        }
+    }
+}
+
+pub(crate) enum UringOperationKind {
+    /// Asynchronous `read(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_read`.
+    Read(ReadData<'static>),
+    /// Asynchronous `write(2).
+    ///
+    /// Equivalent to `io_uring_prep_write`
+    Write(WriteData<'static>),
+    /// Asynchronous `fsync(2)`.
+    ///
     /// Equivalent to `io_uring_prep_fsync`
     Fsync(FsyncData),
     /// Asynchronous `fdatasync(2)`.
@@ -264,6 +2154,194 @@ pub(crate) enum UringOperationKind {
     ///
     /// Equivalent to `io_uring_prep_madvise`.
     Madvise(MadviseData),
+    /// Asynchronous `posix_fadvise(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_fadvise`.
+    Fadvise(FadviseData),
+    /// Asynchronous `ftruncate(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_ftruncate`.
+    Ftruncate(FtruncateData),
+    /// Asynchronous multishot `accept4(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_multishot_accept`.
+    MultishotAccept(MultishotAcceptData),
+    /// Cancellation of another operation issued by this library.
+    ///
+    /// Equivalent to `io_uring_prep_cancel64`.
+    Cancel,
+    /// Timeout of the preceding linked operation issued by this library.
+    ///
+    /// Equivalent to `io_uring_prep_link_timeout`.
+    LinkTimeout(Box<__kernel_timespec>),
+    /// Asynchronous `shutdown(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_shutdown`.
+    Shutdown(ShutdownData),
+    /// Asynchronous `socket(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_socket`.
+    Socket(SocketData),
+    /// Asynchronous `close(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_close`.
+    Close(CloseData),
+    /// Asynchronous `recv(2)` into a buffer selected from the group `bgid`.
+    ///
+    /// Equivalent to `io_uring_prep_recv` with `IOSQE_BUFFER_SELECT`.
+    Recv { bgid: u16 },
+    /// Asynchronous multishot `recv(2)` into buffers selected from the group `bgid`.
+    ///
+    /// Equivalent to `io_uring_prep_recv_multishot` with `IOSQE_BUFFER_SELECT`.
+    MultishotRecv { bgid: u16 },
+    /// Asynchronous `sendmsg(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_sendmsg`.
+    Sendmsg(SendmsgData),
+    /// Asynchronous `recvmsg(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_recvmsg`.
+    Recvmsg(RecvmsgData),
+    /// Asynchronous `renameat2(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_renameat`.
+    Rename(RenameData),
+    /// Asynchronous `unlinkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_unlinkat`.
+    Unlink(UnlinkData),
+    /// Asynchronous `mkdirat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_mkdirat`.
+    Mkdir(MkdirData),
+    /// Asynchronous `symlinkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_symlinkat`.
+    Symlink(SymlinkData),
+    /// Asynchronous `linkat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_linkat`.
+    Link(LinkData),
+    /// Asynchronous no-op.
+    ///
+    /// Equivalent to `io_uring_prep_nop`.
+    Nop(NopData),
+    /// Asynchronous `splice(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_splice`.
+    Splice(SpliceData),
+    /// Asynchronous `tee(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_tee`.
+    Tee(TeeData),
+    /// Asynchronous message to another ring.
+    ///
+    /// Equivalent to `io_uring_prep_msg_ring`.
+    MsgRing(MsgRingData),
+    /// Expected completion posted by another ring with `io_uring_prep_msg_ring`.
+    MsgRingReceive,
+    /// Asynchronous `epoll_ctl(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_epoll_ctl`.
+    EpollCtl(EpollCtlData),
+    /// Asynchronous `waitid(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_waitid`.
+    Waitid(WaitidData),
+    /// Asynchronous `setxattr(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_setxattr`.
+    Setxattr(SetxattrData),
+    /// Asynchronous `getxattr(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_getxattr`.
+    Getxattr(GetxattrData),
+    /// Asynchronous `sync_file_range(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_sync_file_range`.
+    SyncFileRange(SyncFileRangeData),
+    /// Asynchronous zero-copy `send(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_send_zc`.
+    SendZc(SendZcData),
+    /// Asynchronous `openat(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_openat` or `io_uring_prep_openat_direct`.
+    Openat(OpenatData),
+    /// Asynchronous `bind(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_bind`.
+    Bind(BindData),
+    /// Asynchronous `listen(2)`.
+    ///
+    /// Equivalent to `io_uring_prep_listen`.
+    Listen(ListenData),
+}
+
+impl UringOperationKind {
+    /// Returns true if the operation may post multiple completions.
+    pub(crate) fn is_multishot(&self) -> bool {
+        matches!(
+            self,
+            UringOperationKind::MultishotAccept(_) | UringOperationKind::MultishotRecv { .. }
+        )
+    }
+
+    /// Returns the group of the buffer ring the operation selects buffers from, if any.
+    pub(crate) fn buf_group(&self) -> Option<u16> {
+        match self {
+            UringOperationKind::Recv { bgid } | UringOperationKind::MultishotRecv { bgid } => {
+                Some(*bgid)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the operation waits for an external event, e.g. incoming data,
+    /// and thus may never complete on its own.
+    pub(crate) fn waits_for_event(&self) -> bool {
+        matches!(self, UringOperationKind::Recv { .. })
+    }
+
+    /// Releases what the kernel handed over in a completion that nobody is going to take.
+    pub(crate) fn discard(&self, res: i32) {
+        if res < 0 {
+            return;
+        }
+        match self {
+            UringOperationKind::MultishotAccept(_) => unsafe {
+                libc::close(res);
+            },
+            UringOperationKind::Socket(data) if !data.direct => unsafe {
+                libc::close(res);
+            },
+            UringOperationKind::Openat(data) if !data.direct => unsafe {
+                libc::close(res);
+            },
+            UringOperationKind::Recvmsg(data) => {
+                for fd in data.msg.fds() {
+                    unsafe { libc::close(fd) };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Updates the owned data with the result of the completed operation.
+    pub(crate) fn complete(&mut self, res: i32) {
+        if res < 0 {
+            return;
+        }
+        match self {
+            UringOperationKind::Read(ReadData {
+                buf: UringReadBuf::Buf(buf),
+                ..
+            }) => buf.assume_filled(res as usize),
+            UringOperationKind::Recvmsg(data) => data.msg.buf.assume_filled(res as usize),
+            UringOperationKind::Getxattr(data) => data.buf.assume_filled(res as usize),
+            _ => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -273,9 +2351,78 @@ mod test {
     #[test]
     fn test_sqe() {
         let _sqe = Sqe::read(0, UringBuf::Vec(vec![]), 0);
+        let mut buf = [0u8; 8];
+        let _sqe = Sqe::read(0, unsafe { UringReadBuf::from_slice(&mut buf[..]) }, 0);
         let _sqe = Sqe::write(0, UringBuf::Vec(vec![]), 0);
+        let _sqe = Sqe::write(0, unsafe { UringWriteBuf::from_slice(&b""[..]) }, 0);
         let _sqe = Sqe::madvise(UringBuf::Vec(vec![]), Madvise::DontNeed);
         let _sqe = Sqe::fsync(0);
         let _sqe = Sqe::fdatasync(0);
+        let _sqe = Sqe::fadvise(0, 0, 0, libc::POSIX_FADV_WILLNEED);
+        let _sqe = Sqe::ftruncate(0, 0);
+        let _sqe = Sqe::multishot_accept(0, libc::SOCK_CLOEXEC);
+        let _sqe = Sqe::shutdown(0, libc::SHUT_WR);
+        let _sqe = Sqe::socket(libc::AF_INET, libc::SOCK_STREAM, 0, 0);
+        let _sqe = Sqe::socket_direct(libc::AF_INET, libc::SOCK_STREAM, 0, 0);
+        let _sqe = Sqe::close(0);
+        let _sqe = Sqe::sendmsg(0, UringBuf::Vec(vec![]), &scm_rights(&[0]), 0);
+        let _sqe = Sqe::recvmsg(0, UringBuf::Vec(vec![0; 8]), 64, 0);
+        let _sqe = Sqe::rename(
+            libc::AT_FDCWD,
+            CString::new("a").unwrap(),
+            libc::AT_FDCWD,
+            CString::new("b").unwrap(),
+            libc::RENAME_NOREPLACE,
+        );
+        let _sqe = Sqe::unlink(libc::AT_FDCWD, CString::new("a").unwrap(), 0);
+        let _sqe = Sqe::mkdir(libc::AT_FDCWD, CString::new("a").unwrap(), 0o755);
+        let _sqe = Sqe::symlink(
+            CString::new("a").unwrap(),
+            libc::AT_FDCWD,
+            CString::new("b").unwrap(),
+        );
+        let _sqe = Sqe::hard_link(
+            libc::AT_FDCWD,
+            CString::new("a").unwrap(),
+            libc::AT_FDCWD,
+            CString::new("b").unwrap(),
+            0,
+        );
+        let _sqe = Sqe::nop();
+        let _sqe = Sqe::splice(0, None, 1, Some(0), 4096, 0);
+        let _sqe = Sqe::tee(0, 1, 4096, 0);
+        let _sqe = Sqe::msg_ring(0, 42, 1);
+        let _sqe = Sqe::epoll_ctl(
+            0,
+            libc::EPOLL_CTL_ADD,
+            1,
+            libc::epoll_event {
+                events: libc::EPOLLIN as u32,
+                u64: 0,
+            },
+        );
+        let _sqe = Sqe::waitid(libc::P_ALL, 0, libc::WEXITED);
+        let _sqe = Sqe::setxattr(
+            CString::new("/tmp").unwrap(),
+            CString::new("user.test").unwrap(),
+            UringBuf::Vec(b"value".to_vec()),
+            0,
+        );
+        let _sqe = Sqe::getxattr(
+            CString::new("/tmp").unwrap(),
+            CString::new("user.test").unwrap(),
+            UringBuf::Vec(vec![0; 64]),
+        );
+        let _sqe = Sqe::sync_file_range(0, 0, 4096, libc::SYNC_FILE_RANGE_WRITE);
+        let _sqe = Sqe::send_zc(0, UringBuf::Vec(b"hello".to_vec()), 0, 0);
+        let _sqe = Sqe::openat(
+            libc::AT_FDCWD,
+            CString::new("a").unwrap(),
+            libc::O_RDONLY,
+            0,
+        );
+        let _sqe = Sqe::read(0, UringBuf::Vec(vec![0; 8]), 0).fixed_file();
+        let _sqe = Sqe::bind(0, "127.0.0.1:0".parse().unwrap());
+        let _sqe = Sqe::listen(0, 128);
     }
 }
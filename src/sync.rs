@@ -0,0 +1,69 @@
+//! Sharing a [`Uring`] between threads.
+use std::{
+    ops::Deref,
+    sync::{Mutex, MutexGuard, TryLockError},
+};
+
+use crate::{Result, Uring};
+
+/// A [`Uring`] that can be shared between threads.
+///
+/// The ring is behind a mutex. A thread locks it with [`lock`](SyncUring::lock) and
+/// uses the guard as a `Uring`. Handles borrow the guard, so an operation is prepared,
+/// submitted and waited for under one lock, and a thread blocked in `wait` keeps the
+/// other threads out. To keep the lock short, wait only for operations that complete
+/// quickly, or take the guard repeatedly and process the completions with
+/// [`Uring::completions`].
+///
+/// Several threads submit to the ring, so it is created without
+/// `IORING_SETUP_SINGLE_ISSUER`, and [`Uring::register_ring_fd`] must not be used.
+pub struct SyncUring(Mutex<SendUring>);
+
+struct SendUring(Uring);
+
+// SAFETY: `Uring` is not `Send` as its state keeps raw pointers into the memory of
+// the operations, and the ring itself is not synchronized. Here, the `Uring` is only
+// reached through the mutex, and the handles borrow the guard, which cannot leave the
+// locking thread; nothing refers to the `Uring` when another thread takes the lock.
+unsafe impl Send for SendUring {}
+
+impl SyncUring {
+    /// Creates a new `SyncUring` with `entries` SQ entries.
+    pub fn new(entries: usize) -> Result<SyncUring> {
+        Ok(SyncUring(Mutex::new(SendUring(Uring::new(entries)?))))
+    }
+
+    /// Locks the ring, blocking until no other thread uses it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while holding the lock, as the state of the ring
+    /// may be inconsistent.
+    pub fn lock(&self) -> SyncUringGuard<'_> {
+        SyncUringGuard(self.0.lock().expect("SyncUring poisoned"))
+    }
+
+    /// Locks the ring if no other thread uses it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread panicked while holding the lock.
+    pub fn try_lock(&self) -> Option<SyncUringGuard<'_>> {
+        match self.0.try_lock() {
+            Ok(guard) => Some(SyncUringGuard(guard)),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(_)) => panic!("SyncUring poisoned"),
+        }
+    }
+}
+
+/// Exclusive access to the [`Uring`] of a [`SyncUring`], released on drop.
+pub struct SyncUringGuard<'a>(MutexGuard<'a, SendUring>);
+
+impl<'a> Deref for SyncUringGuard<'a> {
+    type Target = Uring;
+
+    fn deref(&self) -> &Uring {
+        &self.0 .0
+    }
+}
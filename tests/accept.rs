@@ -0,0 +1,37 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    os::unix::io::AsRawFd,
+};
+
+use aluring::{result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_multishot_accept() {
+    let ring = Uring::new(8).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut handle = ring
+        .prepare_multishot_accept(Sqe::multishot_accept(
+            listener.as_raw_fd(),
+            libc::SOCK_CLOEXEC,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+
+    let _clients = (0..3)
+        .map(|_| TcpStream::connect(addr).unwrap())
+        .collect::<Vec<_>>();
+    for _ in 0..3 {
+        let result = handle.wait().unwrap().unwrap();
+        assert!(result.more());
+        assert_ne!(result.flags(), 0);
+        let fd = result.as_io_result().unwrap();
+        assert!(fd >= 0);
+        unsafe { libc::close(fd) };
+    }
+
+    // Dropping the handle cancels the armed accept.
+    drop(handle);
+    drop(ring);
+}
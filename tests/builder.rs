@@ -0,0 +1,122 @@
+use std::{io::Write, os::unix::io::AsRawFd, time::Duration};
+
+use aluring::{
+    buf::UringBuf,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    Error, SqFullPolicy, Uring,
+};
+
+fn nop(ring: &Uring) {
+    let handle = ring.prepare_nop(Sqe::nop()).unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+}
+
+/// Returns `None` if the running kernel does not know the setup flags.
+fn build(result: aluring::Result<Uring>) -> Option<Uring> {
+    match result {
+        Ok(ring) => Some(ring),
+        Err(Error::InitError(e, _)) if e.raw_os_error() == Some(libc::EINVAL) => None,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn test_single_issuer_coop_taskrun() {
+    if let Some(ring) = build(Uring::builder(8).single_issuer().coop_taskrun().build()) {
+        nop(&ring);
+    }
+}
+
+#[test]
+fn test_defer_taskrun() {
+    if let Some(ring) = build(Uring::builder(8).defer_taskrun().build()) {
+        nop(&ring);
+    }
+}
+
+#[test]
+fn test_sqpoll() {
+    let ring = match Uring::builder(8).sqpoll(Duration::from_millis(10)).build() {
+        Ok(ring) => ring,
+        // Not permitted for unprivileged users on older kernels.
+        Err(Error::InitError(e, _))
+            if matches!(e.raw_os_error(), Some(libc::EPERM) | Some(libc::EINVAL)) =>
+        {
+            return
+        }
+        Err(e) => panic!("{}", e),
+    };
+    if !ring.features().sqpoll_nonfixed() {
+        return;
+    }
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"0123456789").unwrap();
+
+    for round in 0..2 {
+        let handles = (0..5)
+            .map(|i| {
+                ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 2]), i * 2))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        ring.submit().unwrap();
+        let data = handles
+            .into_iter()
+            .flat_map(|h| h.wait().unwrap().into_buf().as_slice().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(data, b"0123456789");
+        if round == 0 {
+            // Let the polling thread go to sleep, so that submitting wakes it up.
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+#[test]
+fn test_sq_full_auto_submit() {
+    let ring = Uring::builder(2).build().unwrap();
+    let handles = (0..3)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    ring.submit().unwrap();
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+}
+
+#[test]
+fn test_sq_full_error() {
+    let ring = Uring::builder(2)
+        .sq_full_policy(SqFullPolicy::Error)
+        .build()
+        .unwrap();
+    let mut handles = (0..2)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    assert!(matches!(ring.prepare_nop(Sqe::nop()), Err(Error::SqFull)));
+    assert_eq!(ring.sq_ready(), 2);
+
+    assert_eq!(ring.submit().unwrap(), 2);
+    handles.push(ring.prepare_nop(Sqe::nop()).unwrap());
+    ring.submit().unwrap();
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+}
+
+#[test]
+fn test_register_ring_fd() {
+    match Uring::builder(8).register_ring_fd().build() {
+        Ok(ring) => {
+            nop(&ring);
+            // Registering again is a no-op.
+            ring.register_ring_fd().unwrap();
+            nop(&ring);
+        }
+        // io_uring_register_ring_fd requires Linux 5.18.
+        Err(Error::RegisterRingFdError(e)) => assert_eq!(e.raw_os_error(), Some(libc::EINVAL)),
+        Err(e) => panic!("{}", e),
+    }
+}
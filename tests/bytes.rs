@@ -0,0 +1,84 @@
+#![cfg(feature = "bytes")]
+
+use std::{io::Write, os::unix::io::AsRawFd};
+
+use aluring::{
+    buf::UringBuf,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    Uring,
+};
+use bytes::BytesMut;
+
+#[test]
+fn test_read_bytes_mut() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let s = "hello, world\n";
+    f.write_all(s.as_bytes()).unwrap();
+
+    let mut buf = BytesMut::with_capacity(64);
+    buf.extend_from_slice(b"> ");
+    let handle = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::BytesMut(buf), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), s.len());
+    match result.into_buf() {
+        UringBuf::BytesMut(buf) => assert_eq!(&buf.freeze()[..], b"> hello, world\n"),
+        _ => panic!("expected UringBuf::BytesMut"),
+    }
+}
+
+#[test]
+fn test_recycle_bytes_mut() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(
+            f.as_raw_fd(),
+            UringBuf::BytesMut(BytesMut::with_capacity(5)),
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let handle = handle
+        .wait()
+        .unwrap()
+        .recycle(&ring, f.as_raw_fd(), 7)
+        .unwrap();
+    ring.submit().unwrap();
+    match handle.wait().unwrap().into_buf() {
+        UringBuf::BytesMut(buf) => assert_eq!(&buf[..], b"world"),
+        _ => panic!("expected UringBuf::BytesMut"),
+    }
+}
+
+#[test]
+fn test_split_bytes_mut() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(
+            f.as_raw_fd(),
+            UringBuf::from(BytesMut::with_capacity(64)),
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let mut buf = match handle.wait().unwrap().into_buf() {
+        UringBuf::BytesMut(buf) => buf,
+        _ => panic!("expected UringBuf::BytesMut"),
+    };
+
+    // Forward the received bytes and reuse the remaining capacity.
+    let received = buf.split().freeze();
+    assert_eq!(&received[..], b"hello, world");
+    assert!(buf.is_empty());
+    assert!(buf.capacity() >= 64 - received.len());
+}
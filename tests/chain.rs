@@ -0,0 +1,114 @@
+use std::{fs, os::unix::io::AsRawFd};
+
+use aluring::{
+    buf::UringBuf,
+    result::{BufIoResult, IoResult, UringResult},
+    sqe::Sqe,
+    Error, Uring,
+};
+
+#[test]
+fn test_write_then_fdatasync() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let handles = ring
+        .chain()
+        .push(Sqe::write(
+            f.as_raw_fd(),
+            UringBuf::Vec(b"hello".to_vec()),
+            0,
+        ))
+        .push(Sqe::fdatasync(f.as_raw_fd()))
+        .prepare()
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+
+    let mut handles = handles.into_iter();
+    match handles.next().unwrap().wait().unwrap() {
+        UringResult::Write(result) => assert_eq!(result.as_io_result().unwrap(), 5),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match handles.next().unwrap().wait().unwrap() {
+        UringResult::Fdatasync(result) => result.as_io_result().unwrap(),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(fs::read(f.path()).unwrap(), b"hello");
+}
+
+#[test]
+fn test_read_sees_linked_write() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let handles = ring
+        .chain()
+        .push(Sqe::write(
+            f.as_raw_fd(),
+            UringBuf::Vec(b"hello".to_vec()),
+            0,
+        ))
+        // Starts only after the write completed, so it reads what was written.
+        .push(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 5]), 0))
+        .push(Sqe::fsync(f.as_raw_fd()))
+        .prepare()
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 3);
+
+    let mut handles = handles.into_iter();
+    match handles.next().unwrap().wait().unwrap() {
+        UringResult::Write(result) => assert_eq!(result.as_io_result().unwrap(), 5),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match handles.next().unwrap().wait().unwrap() {
+        UringResult::Read(result) => assert_eq!(result.into_buf().as_slice(), b"hello"),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match handles.next().unwrap().wait().unwrap() {
+        UringResult::Fsync(result) => result.as_io_result().unwrap(),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_failure_cancels_rest_of_chain() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let handles = ring
+        .chain()
+        // Fails with EBADF, so the write is not issued.
+        .push(Sqe::fsync(i32::MAX))
+        .push(Sqe::write(
+            f.as_raw_fd(),
+            UringBuf::Vec(b"hello".to_vec()),
+            0,
+        ))
+        .prepare()
+        .unwrap();
+    ring.submit().unwrap();
+
+    let results = handles
+        .into_iter()
+        .map(|h| h.wait().unwrap())
+        .collect::<Vec<_>>();
+    match &results[..] {
+        [UringResult::Fsync(fsync), UringResult::Write(write)] => {
+            assert_eq!(
+                fsync.as_io_result().unwrap_err().raw_os_error(),
+                Some(libc::EBADF)
+            );
+            assert_eq!(
+                write.as_io_result().unwrap_err().raw_os_error(),
+                Some(libc::ECANCELED)
+            );
+        }
+        results => panic!("unexpected results: {:?}", results),
+    }
+    assert!(fs::read(f.path()).unwrap().is_empty());
+}
+
+#[test]
+fn test_chain_longer_than_sq() {
+    let ring = Uring::new(2).unwrap();
+    let chain = (0..3).fold(ring.chain(), |chain, _| chain.push(Sqe::nop()));
+    assert!(matches!(chain.prepare(), Err(Error::InvalidSqe(_))));
+    assert_eq!(ring.sq_ready(), 0);
+}
@@ -0,0 +1,64 @@
+use std::os::unix::io::AsRawFd;
+
+use aluring::{
+    buf::UringBuf,
+    handle::{OperationKind, UringHandle},
+    sqe::Sqe,
+    Uring,
+};
+
+#[test]
+fn test_debug() {
+    let ring = Uring::new(8).unwrap();
+    let handle = ring.prepare_nop(Sqe::nop()).unwrap();
+    assert_eq!(
+        format!("{:?}", ring),
+        "Uring { entries: 8, submitted_count: 0, operations: 1 }"
+    );
+    assert_eq!(
+        format!("{:?}", handle),
+        format!(
+            "NopHandle(Handle {{ id: {}, observed: false }})",
+            handle.id()
+        )
+    );
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(
+        format!("{:?}", result),
+        "NopResult { res: 0, flags: 0, tag: None }"
+    );
+}
+
+#[test]
+fn test_operation_id() {
+    let ring = Uring::new(8).unwrap();
+    let a = ring.prepare_nop(Sqe::nop()).unwrap();
+    let b = ring.prepare_nop(Sqe::nop()).unwrap();
+    assert_ne!(a.id(), b.id());
+    assert_eq!(a.id().to_string(), u64::from(a.id()).to_string());
+
+    ring.submit().unwrap();
+    while ring.cq_ready() < 2 {
+        std::thread::yield_now();
+    }
+    let mut ids = ring.completions().map(|c| c.unwrap().0).collect::<Vec<_>>();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![a.id(), b.id()]);
+}
+
+#[test]
+fn test_operation_kind() {
+    let ring = Uring::new(8).unwrap();
+    let f = std::fs::File::open("/dev/null").unwrap();
+    let nop = ring.prepare_nop(Sqe::nop()).unwrap();
+    let read = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 8]), 0))
+        .unwrap();
+    assert_eq!(nop.kind(), OperationKind::Nop);
+    assert_eq!(read.kind(), OperationKind::Read);
+
+    let handles: Vec<UringHandle> = vec![nop.into(), read.into()];
+    let kinds = handles.iter().map(|h| h.kind()).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![OperationKind::Nop, OperationKind::Read]);
+}
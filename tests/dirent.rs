@@ -0,0 +1,108 @@
+use std::{ffi::CString, fs, os::unix::ffi::OsStrExt, path::Path};
+
+use aluring::{result::IoResult, sqe::Sqe, Uring};
+
+fn cstring(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap()
+}
+
+#[test]
+fn test_directory_entries() {
+    let ring = Uring::new(8).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    let file = sub.join("file");
+    let hard = sub.join("hard");
+    let sym = sub.join("sym");
+
+    let handle = ring
+        .prepare_mkdir(Sqe::mkdir(libc::AT_FDCWD, cstring(&sub), 0o755))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+    assert!(sub.is_dir());
+
+    fs::write(&file, b"hello").unwrap();
+    let link = ring
+        .prepare_hard_link(Sqe::hard_link(
+            libc::AT_FDCWD,
+            cstring(&file),
+            libc::AT_FDCWD,
+            cstring(&hard),
+            0,
+        ))
+        .unwrap();
+    let symlink = ring
+        .prepare_symlink(Sqe::symlink(
+            CString::new("file").unwrap(),
+            libc::AT_FDCWD,
+            cstring(&sym),
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(link.wait().unwrap().as_io_result().is_ok());
+    assert!(symlink.wait().unwrap().as_io_result().is_ok());
+    assert_eq!(fs::read(&hard).unwrap(), b"hello");
+    assert_eq!(fs::read_link(&sym).unwrap(), Path::new("file"));
+    assert_eq!(fs::read(&sym).unwrap(), b"hello");
+
+    let mut handles = vec![];
+    for path in [&file, &hard, &sym] {
+        handles.push(
+            ring.prepare_unlink(Sqe::unlink(libc::AT_FDCWD, cstring(path), 0))
+                .unwrap(),
+        );
+    }
+    ring.submit().unwrap();
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+    assert_eq!(fs::read_dir(&sub).unwrap().count(), 0);
+
+    let handle = ring
+        .prepare_unlink(Sqe::unlink(
+            libc::AT_FDCWD,
+            cstring(&sub),
+            libc::AT_REMOVEDIR,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+    assert!(!sub.exists());
+}
+
+#[test]
+fn test_linked_mkdir_rename_rmdir() {
+    let ring = Uring::new(8).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old");
+    let new = dir.path().join("new");
+
+    let mkdir = ring
+        .prepare_mkdir(Sqe::mkdir(libc::AT_FDCWD, cstring(&old), 0o755).link())
+        .unwrap();
+    let rename = ring
+        .prepare_rename(
+            Sqe::rename(
+                libc::AT_FDCWD,
+                cstring(&old),
+                libc::AT_FDCWD,
+                cstring(&new),
+                0,
+            )
+            .link(),
+        )
+        .unwrap();
+    let rmdir = ring
+        .prepare_unlink(Sqe::unlink(
+            libc::AT_FDCWD,
+            cstring(&new),
+            libc::AT_REMOVEDIR,
+        ))
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 3);
+    assert!(mkdir.wait().unwrap().as_io_result().is_ok());
+    assert!(rename.wait().unwrap().as_io_result().is_ok());
+    assert!(rmdir.wait().unwrap().as_io_result().is_ok());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
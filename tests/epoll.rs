@@ -0,0 +1,101 @@
+use std::{
+    fs::File,
+    io::Write,
+    os::unix::io::{AsRawFd, FromRawFd},
+};
+
+use aluring::{probe::OpKind, result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_epoll_ctl() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::EpollCtl) {
+        return;
+    }
+    let epoll = unsafe { File::from_raw_fd(libc::epoll_create1(libc::EPOLL_CLOEXEC)) };
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (r, mut w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+
+    let event = libc::epoll_event {
+        events: libc::EPOLLIN as u32,
+        u64: 42,
+    };
+    let handle = ring
+        .prepare_epoll_ctl(Sqe::epoll_ctl(
+            epoll.as_raw_fd(),
+            libc::EPOLL_CTL_ADD,
+            r.as_raw_fd(),
+            event,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    handle.wait().unwrap().as_io_result().unwrap();
+
+    w.write_all(b"x").unwrap();
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+    let n = unsafe { libc::epoll_wait(epoll.as_raw_fd(), events.as_mut_ptr(), 4, 1000) };
+    assert_eq!(n, 1);
+    let data = events[0].u64;
+    assert_eq!(data, 42);
+}
+
+#[test]
+fn test_epoll_ctl_batch() {
+    let ring = Uring::new(64).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::EpollCtl) {
+        return;
+    }
+    let epoll = unsafe { File::from_raw_fd(libc::epoll_create1(libc::EPOLL_CLOEXEC)) };
+    let pipes = (0..32)
+        .map(|_| {
+            let mut fds = [0; 2];
+            assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+            unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) }
+        })
+        .collect::<Vec<_>>();
+
+    // Registers every read end with a single submission.
+    let handles = pipes
+        .iter()
+        .enumerate()
+        .map(|(i, (r, _))| {
+            let event = libc::epoll_event {
+                events: libc::EPOLLIN as u32,
+                u64: i as u64,
+            };
+            ring.prepare_epoll_ctl(Sqe::epoll_ctl(
+                epoll.as_raw_fd(),
+                libc::EPOLL_CTL_ADD,
+                r.as_raw_fd(),
+                event,
+            ))
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ring.submit().unwrap(), pipes.len());
+    for handle in handles {
+        handle.wait().unwrap().as_io_result().unwrap();
+    }
+
+    let handle = ring
+        .prepare_epoll_ctl(Sqe::epoll_ctl(
+            epoll.as_raw_fd(),
+            libc::EPOLL_CTL_DEL,
+            pipes[0].0.as_raw_fd(),
+            libc::epoll_event { events: 0, u64: 0 },
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    handle.wait().unwrap().as_io_result().unwrap();
+
+    for (_, w) in &pipes[..2] {
+        let mut w: &File = w;
+        w.write_all(b"x").unwrap();
+    }
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+    let n = unsafe { libc::epoll_wait(epoll.as_raw_fd(), events.as_mut_ptr(), 4, 1000) };
+    assert_eq!(n, 1);
+    let data = events[0].u64;
+    assert_eq!(data, 1);
+}
@@ -0,0 +1,38 @@
+use std::{io::Write, os::unix::io::AsRawFd};
+
+use aluring::{
+    result::IoResult,
+    sqe::{FadviseData, Sqe},
+    Uring,
+};
+
+const FILE_SIZE: usize = 1024 * 1024;
+
+#[test]
+fn test_fadvise() {
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(&vec![0xa; FILE_SIZE]).unwrap();
+    f.flush().unwrap();
+
+    let ring = Uring::new(8).unwrap();
+    let mut handles = vec![];
+    for advice in [
+        libc::POSIX_FADV_SEQUENTIAL,
+        libc::POSIX_FADV_WILLNEED,
+        libc::POSIX_FADV_DONTNEED,
+    ] {
+        handles.push(
+            ring.prepare_fadvise(Sqe::new(FadviseData {
+                fd: f.as_raw_fd(),
+                offset: 0,
+                len: FILE_SIZE as u64,
+                advice,
+            }))
+            .unwrap(),
+        );
+    }
+    ring.submit().unwrap();
+    for h in handles {
+        assert!(h.wait().unwrap().as_io_result().is_ok());
+    }
+}
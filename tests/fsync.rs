@@ -30,7 +30,7 @@ fn test_barrier_fsync() {
         handles.push(
             ring.prepare_write(Sqe::new(WriteData {
                 fd: f.as_raw_fd(),
-                buf: UringBuf::Vec(buf),
+                buf: UringBuf::Vec(buf).into(),
                 offset,
             }))
             .unwrap(),
@@ -49,3 +49,25 @@ fn test_barrier_fsync() {
         assert!(h.wait().unwrap().as_io_result().is_ok());
     }
 }
+
+#[test]
+fn test_sync_file_range() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let write = ring
+        .prepare_write(Sqe::write(f.as_raw_fd(), UringBuf::Vec(vec![0xa; 8192]), 0).link())
+        .unwrap();
+    let sync = ring
+        .prepare_sync_file_range(Sqe::sync_file_range(
+            f.as_raw_fd(),
+            4096,
+            4096,
+            libc::SYNC_FILE_RANGE_WAIT_BEFORE
+                | libc::SYNC_FILE_RANGE_WRITE
+                | libc::SYNC_FILE_RANGE_WAIT_AFTER,
+        ))
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert_eq!(write.wait().unwrap().as_io_result().unwrap(), 8192);
+    sync.wait().unwrap().as_io_result().unwrap();
+}
@@ -0,0 +1,44 @@
+use std::{io::Write, os::unix::io::AsRawFd};
+
+use aluring::{probe::OpKind, result::IoResult, sqe::Sqe, Error, Uring};
+
+#[test]
+fn test_ftruncate_then_fsync() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::Ftruncate) {
+        return;
+    }
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(&[0xa; 4096]).unwrap();
+
+    let truncate = ring
+        .prepare_ftruncate(Sqe::ftruncate(f.as_raw_fd(), 1024).link())
+        .unwrap();
+    let fsync = ring.prepare_fsync(Sqe::fsync(f.as_raw_fd())).unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+
+    assert!(fsync.wait().unwrap().as_io_result().is_ok());
+    assert!(truncate.wait().unwrap().as_io_result().is_ok());
+    assert_eq!(f.as_file().metadata().unwrap().len(), 1024);
+}
+
+#[test]
+fn test_ftruncate_to_zero() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(&[0xa; 4096]).unwrap();
+
+    let supported = ring.probe().unwrap().supports(OpKind::Ftruncate);
+    match ring.prepare_ftruncate(Sqe::ftruncate(f.as_raw_fd(), 0)) {
+        Ok(handle) => {
+            assert!(supported);
+            ring.submit().unwrap();
+            assert!(handle.wait().unwrap().as_io_result().is_ok());
+            assert_eq!(f.as_file().metadata().unwrap().len(), 0);
+        }
+        Err(e) => {
+            assert!(!supported);
+            assert!(matches!(e, Error::Unsupported(OpKind::Ftruncate)));
+        }
+    }
+}
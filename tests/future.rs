@@ -0,0 +1,51 @@
+#![cfg(feature = "async")]
+
+use std::{
+    future::Future,
+    io::Write,
+    os::unix::io::AsRawFd,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use aluring::{buf::UringBuf, result::BufIoResult, sqe::Sqe, Uring};
+
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_read_future() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    let flag = Arc::new(Flag(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 5]), 0))
+        .unwrap()
+        .into_future();
+    assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+    // Drive the ring outside of the future.
+    ring.submit().unwrap();
+    while !flag.0.load(Ordering::SeqCst) {
+        ring.completions().for_each(|c| drop(c.unwrap()));
+    }
+
+    match Pin::new(&mut future).poll(&mut cx) {
+        Poll::Ready(result) => assert_eq!(result.unwrap().into_buf().as_slice(), b"hello"),
+        Poll::Pending => panic!("the read is completed"),
+    }
+}
@@ -0,0 +1,58 @@
+use std::ptr;
+
+use aluring::{
+    buf::UringBuf,
+    sqe::{Madvise, Sqe},
+    Error, Uring,
+};
+
+fn assert_invalid<T>(result: aluring::Result<T>, reason: &str) {
+    match result {
+        Err(Error::InvalidSqe(r)) => assert_eq!(r, reason),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("invalid SQE was accepted"),
+    }
+}
+
+#[test]
+fn test_negative_fd() {
+    let ring = Uring::new(8).unwrap();
+    assert_invalid(
+        ring.prepare_read(Sqe::read(-1, UringBuf::Vec(vec![0; 8]), 0)),
+        "negative fd",
+    );
+    assert_invalid(ring.prepare_fsync(Sqe::fsync(-1)), "negative fd");
+    assert_invalid(
+        ring.prepare_splice(Sqe::splice(0, None, -1, None, 8, 0)),
+        "negative fd",
+    );
+    assert_eq!(ring.submit().unwrap(), 0);
+}
+
+#[test]
+fn test_empty_raw_buffer() {
+    let ring = Uring::new(8).unwrap();
+    let mut buf = [0u8; 8];
+    assert_invalid(
+        ring.prepare_write(Sqe::write(
+            1,
+            UringBuf::Raw {
+                ptr: buf.as_mut_ptr(),
+                len: 0,
+            },
+            0,
+        )),
+        "empty raw buffer",
+    );
+    assert_invalid(
+        ring.prepare_madvise(Sqe::madvise(
+            UringBuf::Raw {
+                ptr: ptr::null_mut(),
+                len: 4096,
+            },
+            Madvise::WillNeed,
+        )),
+        "empty raw buffer",
+    );
+    assert_eq!(ring.submit().unwrap(), 0);
+}
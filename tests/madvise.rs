@@ -1,8 +1,8 @@
 use std::{fs::File, io::Write, os::unix::io::AsRawFd, ptr, time::Instant};
 
 use aluring::{
-    buf::UringBuf,
-    result::IoResult,
+    buf::{MemRegion, UringBuf},
+    result::{BufIoResult, IoResult},
     sqe::{Madvise, MadviseData, Sqe},
     Uring,
 };
@@ -44,7 +44,8 @@ fn test_madvise() {
                 buf: UringBuf::Raw {
                     ptr,
                     len: FILE_SIZE,
-                },
+                }
+                .into(),
             }))
             .unwrap()
             .wait()
@@ -87,7 +88,8 @@ fn test_madvise() {
             buf: UringBuf::Raw {
                 ptr,
                 len: ARENA_SIZE,
-            },
+            }
+            .into(),
         }))
         .unwrap()
         .wait()
@@ -101,3 +103,81 @@ fn test_madvise() {
         libc::munmap(ptr as *mut _, ARENA_SIZE);
     }
 }
+
+#[test]
+fn test_madvise_advice() {
+    const LEN: usize = 1024 * 1024;
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(&vec![0xa; LEN]).unwrap();
+    f.flush().unwrap();
+
+    let ring = Uring::new(8).unwrap();
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            LEN,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            f.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+
+    let madvise = |advise| {
+        ring.prepare_madvise(Sqe::new(MadviseData {
+            advise,
+            buf: UringBuf::Raw {
+                ptr: ptr as *mut u8,
+                len: LEN,
+            }
+            .into(),
+        }))
+        .unwrap()
+        .wait()
+        .unwrap()
+        .as_io_result()
+    };
+    madvise(Madvise::WillNeed).unwrap();
+    for advise in [Madvise::Cold, Madvise::PageOut] {
+        if let Err(e) = madvise(advise) {
+            // Requires Linux 5.4.
+            assert_eq!(e.raw_os_error(), Some(libc::EINVAL));
+        }
+    }
+
+    unsafe { libc::munmap(ptr, LEN) };
+}
+
+#[test]
+fn test_madvise_read_only_region() {
+    const LEN: usize = 64 * 1024;
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(&vec![0xa; LEN]).unwrap();
+    f.flush().unwrap();
+
+    let ring = Uring::new(8).unwrap();
+    let addr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            LEN,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            f.as_raw_fd(),
+            0,
+        )
+    };
+    assert_ne!(addr, libc::MAP_FAILED);
+
+    let region = MemRegion::new(addr as *const u8, LEN);
+    let result = ring
+        .prepare_madvise(Sqe::madvise(region, Madvise::WillNeed))
+        .unwrap()
+        .wait()
+        .unwrap();
+    result.as_io_result().unwrap();
+    // The region is not handed out, so it cannot be reused as a read buffer.
+    assert!(result.into_buf().as_slice().is_empty());
+
+    unsafe { libc::munmap(addr, LEN) };
+}
@@ -0,0 +1,213 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::unix::{
+        io::{AsRawFd, FromRawFd},
+        net::UnixStream,
+    },
+};
+
+use aluring::{
+    buf::UringBuf,
+    result::{BufIoResult, IoResult},
+    sqe::{scm_rights, Sqe},
+    Uring,
+};
+
+#[test]
+fn test_pass_fd() {
+    let ring = Uring::new(8).unwrap();
+    let (a, b) = UnixStream::pair().unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let mut pipe_r = unsafe { File::from_raw_fd(fds[0]) };
+    let pipe_w = unsafe { File::from_raw_fd(fds[1]) };
+
+    let send = ring
+        .prepare_sendmsg(Sqe::sendmsg(
+            a.as_raw_fd(),
+            UringBuf::Vec(b"x".to_vec()),
+            &scm_rights(&[pipe_w.as_raw_fd()]),
+            0,
+        ))
+        .unwrap();
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            64,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert_eq!(send.wait().unwrap().as_io_result().unwrap(), 1);
+    let result = recv.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 1);
+
+    let received = result.fds();
+    assert_eq!(received.len(), 1);
+    assert_eq!(&result.into_buf().as_slice()[..1], b"x");
+    drop(pipe_w);
+
+    let mut passed = unsafe { File::from_raw_fd(received[0]) };
+    passed.write_all(b"hello").unwrap();
+    drop(passed);
+    let mut s = String::new();
+    pipe_r.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "hello");
+}
+
+#[test]
+fn test_pass_pipe_fds_cloexec() {
+    let ring = Uring::new(8).unwrap();
+    let (a, b) = UnixStream::pair().unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+    let send = ring
+        .prepare_sendmsg(Sqe::sendmsg(
+            a.as_raw_fd(),
+            UringBuf::Vec(b"x".to_vec()),
+            &scm_rights(&fds),
+            0,
+        ))
+        .unwrap();
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            64,
+            libc::MSG_CMSG_CLOEXEC,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(send.wait().unwrap().as_io_result().is_ok());
+    let result = recv.wait().unwrap();
+    assert!(!result.control().is_empty());
+
+    let received = result.fds();
+    assert_eq!(received.len(), 2);
+    for fd in received.iter().chain(fds.iter()) {
+        if received.contains(fd) {
+            assert_ne!(
+                unsafe { libc::fcntl(*fd, libc::F_GETFD) } & libc::FD_CLOEXEC,
+                0
+            );
+        }
+        unsafe { libc::close(*fd) };
+    }
+}
+
+#[test]
+fn test_drop_ring_with_idle_recvmsg() {
+    let ring = Uring::new(8).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    // Nothing is ever sent, so the operation only completes by cancellation.
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            0,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    drop(recv);
+
+    let start = std::time::Instant::now();
+    drop(ring);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_close_ring_with_idle_recvmsg() {
+    let ring = Uring::new(8).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            0,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    drop(recv);
+
+    ring.close_ring().unwrap();
+}
+
+#[test]
+fn test_drop_timeout() {
+    let ring = Uring::builder(8)
+        .drop_timeout(Some(std::time::Duration::from_millis(100)))
+        .build()
+        .unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            0,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    drop(recv);
+
+    let start = std::time::Instant::now();
+    drop(ring);
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_wait_unsubmitted_behind_idle_recvmsg() {
+    let ring = Uring::new(8).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let recv = ring
+        .prepare_recvmsg(Sqe::recvmsg(
+            b.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            0,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+
+    // The nop is not submitted yet; waiting must not block on the idle recvmsg.
+    let nop = ring.prepare_nop(Sqe::nop()).unwrap();
+    assert!(nop.wait().unwrap().as_io_result().is_ok());
+
+    ring.cancel_all().unwrap();
+    assert!(recv.wait().unwrap().as_io_result().is_err());
+}
+
+#[test]
+fn test_cancel_all() {
+    let ring = Uring::new(8).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let recvs = (0..2)
+        .map(|_| {
+            ring.prepare_recvmsg(Sqe::recvmsg(
+                b.as_raw_fd(),
+                UringBuf::Vec(vec![0; 16]),
+                0,
+                0,
+            ))
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ring.cancel_all().unwrap(), 2);
+    for recv in recvs {
+        let result = recv.wait().unwrap();
+        assert_eq!(
+            result.as_io_result().unwrap_err().raw_os_error(),
+            Some(libc::ECANCELED)
+        );
+    }
+}
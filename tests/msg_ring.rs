@@ -0,0 +1,48 @@
+use aluring::{probe::OpKind, result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_msg_ring() {
+    let source = Uring::new(8).unwrap();
+    if !source.probe().unwrap().supports(OpKind::MsgRing) {
+        return;
+    }
+    let target = Uring::new(8).unwrap();
+
+    let receiver = target.expect_msg_ring().unwrap();
+    let sender = source
+        .prepare_msg_ring(Sqe::msg_ring(target.ring_fd(), 42, receiver.id().into()))
+        .unwrap();
+    source.submit().unwrap();
+    assert!(sender.wait().unwrap().as_io_result().is_ok());
+
+    let message = receiver.wait().unwrap();
+    assert_eq!(message.value(), 42);
+    assert_eq!(message.as_io_result().unwrap(), 42);
+}
+
+#[test]
+fn test_drop_msg_ring_receiver() {
+    let source = Uring::new(8).unwrap();
+    if !source.probe().unwrap().supports(OpKind::MsgRing) {
+        return;
+    }
+    let target = Uring::new(8).unwrap();
+
+    // No SQE is submitted for the message, so it is neither in flight nor cancelled.
+    let receiver = target.expect_msg_ring().unwrap();
+    assert_eq!(target.inflight().unwrap(), 0);
+    assert_eq!(target.cancel_all().unwrap(), 0);
+
+    let id = receiver.id();
+    drop(receiver);
+    let sender = source
+        .prepare_msg_ring(Sqe::msg_ring(target.ring_fd(), 42, id.into()))
+        .unwrap();
+    source.submit().unwrap();
+    assert!(sender.wait().unwrap().as_io_result().is_ok());
+
+    // The late message is discarded while waiting for another operation.
+    let nop = target.prepare_nop(Sqe::nop()).unwrap();
+    target.submit().unwrap();
+    assert!(nop.wait().unwrap().as_io_result().is_ok());
+}
@@ -0,0 +1,80 @@
+use aluring::{result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_nops() {
+    const N: usize = 100;
+    let ring = Uring::new(128).unwrap();
+    let handles = (0..N)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ring.submit().unwrap(), N);
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+    assert_eq!(ring.cq_ready(), 0);
+}
+
+#[test]
+fn test_drained_nop() {
+    let ring = Uring::new(8).unwrap();
+    let first = ring.prepare_nop(Sqe::nop()).unwrap();
+    let fence = ring.prepare_nop(Sqe::nop().drain()).unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert!(fence.wait().unwrap().as_io_result().is_ok());
+    assert!(first.observed());
+    assert!(first.wait().unwrap().as_io_result().is_ok());
+}
+
+#[test]
+fn test_submit_all() {
+    const N: usize = 10;
+    let ring = Uring::new(4).unwrap();
+    // Preparing more than the SQ depth submits the earlier entries implicitly.
+    let handles = (0..N)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    let pending = ring.sq_ready();
+    assert!(pending > 0);
+    assert_eq!(ring.submit_all().unwrap(), pending);
+    assert_eq!(ring.sq_ready(), 0);
+    assert_eq!(ring.submit_all().unwrap(), 0);
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+}
+
+#[test]
+fn test_submit_with_status() {
+    let ring = Uring::new(8).unwrap();
+    let status = ring.submit_with_status().unwrap();
+    assert_eq!(status.submitted(), 0);
+    assert!(status.is_complete());
+
+    let handles = (0..3)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    let status = ring.submit_with_status().unwrap();
+    assert_eq!(status.submitted(), 3);
+    assert_eq!(status.pending(), 0);
+    assert!(status.is_complete());
+    for handle in handles {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+    }
+}
+
+#[test]
+fn test_inflight() {
+    let ring = Uring::new(8).unwrap();
+    assert_eq!(ring.inflight().unwrap(), 0);
+    let handles = (0..3)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ring.inflight().unwrap(), 3);
+    ring.submit().unwrap();
+    assert_eq!(ring.inflight().unwrap(), 3);
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert!(handle.wait().unwrap().as_io_result().is_ok());
+        assert!(ring.inflight().unwrap() <= 2 - i);
+    }
+    assert_eq!(ring.inflight().unwrap(), 0);
+}
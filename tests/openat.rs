@@ -0,0 +1,63 @@
+use std::{ffi::CString, fs, io::Read, os::unix::io::FromRawFd};
+
+use aluring::{
+    buf::UringBuf,
+    probe::OpKind,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    Uring,
+};
+
+#[test]
+fn test_openat() {
+    let ring = Uring::new(8).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a");
+    fs::write(&path, b"hello").unwrap();
+
+    let h = ring
+        .prepare_openat(Sqe::openat(
+            libc::AT_FDCWD,
+            CString::new(path.to_str().unwrap()).unwrap(),
+            libc::O_RDONLY | libc::O_CLOEXEC,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let fd = h.wait().unwrap().as_io_result().unwrap();
+    let mut f = unsafe { fs::File::from_raw_fd(fd) };
+    let mut s = String::new();
+    f.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "hello");
+}
+
+#[test]
+fn test_openat_direct() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::Openat) || ring.register_files_sparse(4).is_err() {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a");
+    fs::write(&path, b"hello").unwrap();
+
+    let h = ring
+        .prepare_openat(Sqe::openat_direct(
+            libc::AT_FDCWD,
+            CString::new(path.to_str().unwrap()).unwrap(),
+            libc::O_RDONLY,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let index = h.wait().unwrap().as_io_result().unwrap();
+    assert!((0..4).contains(&index));
+
+    let h = ring
+        .prepare_read(Sqe::read(index, UringBuf::Vec(vec![0; 5]), 0).fixed_file())
+        .unwrap();
+    ring.submit().unwrap();
+    let result = h.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 5);
+    assert_eq!(result.into_buf().as_slice(), b"hello");
+}
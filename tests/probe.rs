@@ -0,0 +1,26 @@
+use aluring::{probe::OpKind, Uring};
+
+#[test]
+fn test_probe() {
+    let ring = Uring::new(8).unwrap();
+    let probe = ring.probe().unwrap();
+    assert!(probe.supports(OpKind::Read));
+    assert!(probe.supports(OpKind::Write));
+    assert!(probe.supports(OpKind::Fsync));
+    assert_eq!(
+        probe.supports(OpKind::Fsync),
+        probe.supports(OpKind::Fdatasync)
+    );
+}
+
+#[test]
+fn test_features() {
+    let ring = Uring::new(8).unwrap();
+    let features = ring.features();
+    // Available since Linux 5.4.
+    assert!(features.single_mmap());
+    // Available since Linux 5.5 and 5.6; reads at the current offset rely on the latter.
+    assert!(features.nodrop());
+    assert!(features.rw_cur_pos());
+    assert_ne!(features.bits(), 0);
+}
@@ -0,0 +1,477 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    os::unix::io::{AsRawFd, FromRawFd},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use aluring::{
+    buf::{UringBuf, UringReadBuf},
+    result::{BufIoResult, IoResult},
+    sqe::{Offset, Sqe},
+    Error, Uring,
+};
+
+/// Counts allocations made on threads that opted in.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ZEROED_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(|c| c.get()).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(|c| c.get()).unwrap_or(false) {
+            ZEROED_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_read_slice() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let s = "hello, world\n";
+    f.write_all(s.as_bytes()).unwrap();
+
+    let mut buf = [0u8; 64];
+    COUNTING.with(|c| c.set(true));
+    let len = {
+        let handle = ring
+            .prepare_read(Sqe::read(
+                f.as_raw_fd(),
+                unsafe { UringReadBuf::from_slice(&mut buf[..]) },
+                0,
+            ))
+            .unwrap();
+        ring.submit().unwrap();
+        handle.wait().unwrap().as_io_result().unwrap()
+    };
+    COUNTING.with(|c| c.set(false));
+
+    assert_eq!(ALLOCATIONS.load(Ordering::SeqCst), 0);
+    assert_eq!(&buf[..len], s.as_bytes());
+}
+
+#[test]
+fn test_into_parts_on_error() {
+    let ring = Uring::new(8).unwrap();
+    let handle = ring
+        .prepare_read(Sqe::read(i32::MAX, UringBuf::Vec(vec![0xa; 16]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBADF));
+    assert_eq!(buf.as_slice(), &[0xau8; 16][..]);
+}
+
+#[test]
+fn test_recycle() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 5]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 5);
+
+    let handle = result.recycle(&ring, f.as_raw_fd(), 7).unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"world");
+}
+
+#[test]
+fn test_borrowed_read_buf_not_returned() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    let mut buf = [0u8; 16];
+    let handle = ring
+        .prepare_read(Sqe::read(
+            f.as_raw_fd(),
+            unsafe { UringReadBuf::from_slice(&mut buf[..]) },
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    assert!(matches!(
+        result.recycle(&ring, f.as_raw_fd(), 0),
+        Err(Error::InvalidSqe(_))
+    ));
+
+    let entry = Sqe::read(
+        f.as_raw_fd(),
+        UringBuf::Raw {
+            ptr: buf.as_mut_ptr(),
+            len: buf.len(),
+        },
+        0,
+    );
+    let handle = ring.prepare_read(entry).unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(matches!(
+        result.recycle(&ring, f.as_raw_fd(), 0),
+        Err(Error::InvalidSqe(_))
+    ));
+}
+
+#[test]
+fn test_short_read_truncates_buf() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 4096]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf.len(), 5);
+    assert_eq!(buf.as_slice(), b"hello");
+
+    let buf = UringBuf::VecRange {
+        vec: vec![0; 16],
+        start: 4,
+        len: 8,
+    };
+    let handle = ring.prepare_read(Sqe::read(f.as_raw_fd(), buf, 3)).unwrap();
+    ring.submit().unwrap();
+    assert_eq!(handle.wait().unwrap().into_buf().as_slice(), b"lo");
+
+    let mut slice = [0u8; 16];
+    let handle = ring
+        .prepare_read(Sqe::read(
+            f.as_raw_fd(),
+            unsafe { UringReadBuf::from_slice(&mut slice[..]) },
+            5,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(result.is_eof());
+    assert!(result.into_buf().as_slice().is_empty());
+}
+
+#[test]
+fn test_wait_raw() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world\n").unwrap();
+
+    let raw = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 64]), 0))
+        .unwrap();
+    let converted = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 64]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let res = raw.wait_raw().unwrap();
+    assert_eq!(
+        res as usize,
+        converted.wait().unwrap().as_io_result().unwrap()
+    );
+    assert_eq!(ring.cq_ready(), 0);
+}
+
+#[test]
+fn test_read_vec_range() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    let buf = UringBuf::VecRange {
+        vec: vec![b'-'; 12],
+        start: 4,
+        len: 5,
+    };
+    let handle = ring.prepare_read(Sqe::read(f.as_raw_fd(), buf, 0)).unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"hello");
+    match buf {
+        UringBuf::VecRange { vec, .. } => assert_eq!(vec, b"----hello---"),
+        _ => panic!("expected UringBuf::VecRange"),
+    }
+}
+
+#[test]
+fn test_vec_range_out_of_bounds() {
+    let ring = Uring::new(8).unwrap();
+    let buf = UringBuf::VecRange {
+        vec: vec![0; 8],
+        start: 4,
+        len: 5,
+    };
+    assert!(matches!(
+        ring.prepare_read(Sqe::read(0, buf, 0)),
+        Err(aluring::Error::InvalidSqe("range out of bounds"))
+    ));
+}
+
+#[test]
+fn test_read_borrowed_fd() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world").unwrap();
+
+    let file = f.reopen().unwrap();
+    let handle = ring
+        .prepare_read(Sqe::read_fd(&file, UringBuf::Vec(vec![0; 5]), 7))
+        .unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"world");
+}
+
+#[test]
+fn test_wait_all() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"0123456789").unwrap();
+
+    let handles = (0..5)
+        .map(|i| {
+            ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 2]), i * 2))
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let results = ring.wait_all(handles).unwrap();
+    let data = results
+        .into_iter()
+        .flat_map(|result| result.into_buf().as_slice().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(data, b"0123456789");
+}
+
+#[test]
+fn test_read_past_eof() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(!result.is_eof());
+    assert_eq!(result.as_io_result().unwrap(), 5);
+
+    let handle = result.recycle(&ring, f.as_raw_fd(), 5).unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(result.is_eof());
+    assert_eq!(result.as_io_result().unwrap(), 0);
+}
+
+#[test]
+fn test_read_uninit() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"hello, world").unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::uninit(64), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 12);
+
+    let handle = result.recycle(&ring, f.as_raw_fd(), 7).unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    // Only the bytes written by the kernel are exposed.
+    assert_eq!(buf.len(), 5);
+    assert_eq!(buf.as_slice(), b"world");
+}
+
+#[test]
+fn test_read_batch() {
+    let ring = Uring::new(64).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    let data = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+    f.write_all(&data).unwrap();
+
+    let entries = (0..1000)
+        .map(|i| Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 1]), i))
+        .collect::<Vec<_>>();
+    let handles = ring.prepare_read_batch(entries).unwrap();
+    assert_eq!(handles.len(), 1000);
+    ring.submit().unwrap();
+
+    for (i, h) in handles.into_iter().enumerate() {
+        let result = h.wait().unwrap();
+        assert_eq!(result.as_io_result().unwrap(), 1);
+        assert_eq!(result.into_buf().as_slice(), &[data[i]]);
+    }
+}
+
+#[test]
+fn test_read_tag() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"0123456789").unwrap();
+
+    let handles = (0..5u64)
+        .map(|i| {
+            let entry = Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 2]), i * 2);
+            ring.prepare_read(entry.tag(100 + i)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let untagged = ring
+        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 2]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+
+    // Waiting in reverse order, each result still carries the tag of its read.
+    for h in handles.into_iter().rev() {
+        let result = h.wait().unwrap();
+        let tag = result.tag().unwrap();
+        let offset = (tag - 100) as usize * 2;
+        assert_eq!(
+            result.into_buf().as_slice(),
+            &b"0123456789"[offset..offset + 2]
+        );
+    }
+    assert_eq!(untagged.wait().unwrap().tag(), None);
+}
+
+#[test]
+fn test_read_uninit_not_zeroed() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"hello").unwrap();
+
+    // `vec![0; n]` would request zeroed memory from the allocator.
+    COUNTING.with(|c| c.set(true));
+    let buf = UringBuf::uninit(1 << 20);
+    COUNTING.with(|c| c.set(false));
+    assert_eq!(ZEROED_ALLOCATIONS.load(Ordering::SeqCst), 0);
+
+    let handle = ring.prepare_read(Sqe::read(f.as_raw_fd(), buf, 0)).unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"hello");
+}
+
+#[test]
+fn test_read_pipe_current_offset() {
+    let ring = Uring::new(8).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (r, mut w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    w.write_all(b"hello, world").unwrap();
+
+    for expected in [&b"hello"[..], &b", wor"[..], &b"ld"[..]] {
+        let handle = ring
+            .prepare_read(Sqe::read(
+                r.as_raw_fd(),
+                UringBuf::Vec(vec![0; 5]),
+                Offset::Current,
+            ))
+            .unwrap();
+        ring.submit().unwrap();
+        let (res, buf) = handle.wait().unwrap().into_parts();
+        let n = res.unwrap();
+        assert_eq!(&buf.as_slice()[..n], expected);
+    }
+}
+
+#[test]
+fn test_read_file_current_offset() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"0123456789").unwrap();
+    f.seek(SeekFrom::Start(2)).unwrap();
+
+    let handle = ring
+        .prepare_read(Sqe::read(
+            f.as_raw_fd(),
+            UringBuf::Vec(vec![0; 3]),
+            Offset::Current,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert_eq!(handle.wait().unwrap().into_buf().as_slice(), b"234");
+    // The read advanced the file position.
+    assert_eq!(f.stream_position().unwrap(), 5);
+}
+
+#[test]
+fn test_try_result() {
+    let ring = Uring::new(8).unwrap();
+    let (r, mut w) = std::os::unix::net::UnixStream::pair().unwrap();
+
+    let mut handle = ring
+        .prepare_read(Sqe::read(
+            r.as_raw_fd(),
+            UringBuf::Vec(vec![0; 16]),
+            Offset::Current,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(handle.try_result().unwrap().is_none());
+
+    w.write_all(b"hello").unwrap();
+    let result = loop {
+        match handle.try_result().unwrap() {
+            Some(result) => break result,
+            None => std::thread::yield_now(),
+        }
+    };
+    let (res, buf) = result.into_parts();
+    assert_eq!(&buf.as_slice()[..res.unwrap()], b"hello");
+    assert!(handle.try_result().is_err());
+}
+
+#[test]
+fn test_read_to_end() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    // Spans several chunks, the last of them partially.
+    let data = (0..300_000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    f.write_all(&data).unwrap();
+
+    assert_eq!(ring.read_to_end(f.as_raw_fd(), 0).unwrap(), data);
+    assert_eq!(
+        ring.read_to_end(f.as_raw_fd(), 1000).unwrap(),
+        &data[1000..]
+    );
+    assert!(ring.read_to_end(f.as_raw_fd(), 300_000).unwrap().is_empty());
+}
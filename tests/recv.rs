@@ -0,0 +1,123 @@
+use std::{
+    io::Write,
+    os::unix::net::{UnixDatagram, UnixStream},
+    os::unix::prelude::AsRawFd,
+};
+
+use aluring::{result::IoResult, sqe::Sqe, Error, Uring};
+
+#[test]
+fn test_recv_buf_ring() {
+    let ring = Uring::new(8).unwrap();
+    let buf_ring = ring.register_buf_ring(4, 64, 1).unwrap();
+    let (mut a, b) = UnixStream::pair().unwrap();
+
+    for msg in [&b"hello"[..], &b"world"[..]] {
+        a.write_all(msg).unwrap();
+        let handle = ring
+            .prepare_recv(Sqe::recv(b.as_raw_fd(), &buf_ring, 0))
+            .unwrap();
+        ring.submit().unwrap();
+        let result = handle.wait().unwrap();
+        assert!(result.buffer_id().unwrap() < 4);
+        assert_eq!(buf_ring.get(&result).unwrap(), msg);
+        buf_ring.recycle(result);
+    }
+}
+
+#[test]
+fn test_buf_ring_invalid_entries() {
+    let ring = Uring::new(8).unwrap();
+    for entries in [0, 3, 65535] {
+        assert!(matches!(
+            ring.register_buf_ring(entries, 64, 1),
+            Err(Error::InvalidSqe(_))
+        ));
+    }
+    // The group is still free.
+    ring.register_buf_ring(4, 64, 1).unwrap();
+}
+
+#[test]
+fn test_drop_pending_recv() {
+    let ring = Uring::new(8).unwrap();
+    let buf_ring = ring.register_buf_ring(4, 64, 1).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let handle = ring
+        .prepare_recv(Sqe::recv(b.as_raw_fd(), &buf_ring, 0))
+        .unwrap();
+    ring.submit().unwrap();
+    // Nothing is ever sent; dropping must cancel rather than block.
+    drop(handle);
+}
+
+#[test]
+fn test_drop_buf_ring_after_leaked_recv() {
+    let ring = Uring::new(8).unwrap();
+    let buf_ring = ring.register_buf_ring(4, 64, 1).unwrap();
+    let (_a, b) = UnixStream::pair().unwrap();
+
+    let handle = ring
+        .prepare_recv(Sqe::recv(b.as_raw_fd(), &buf_ring, 0))
+        .unwrap();
+    ring.submit().unwrap();
+    std::mem::forget(handle);
+    // The ring cancels the leaked recv before its buffers go away, and unregisters
+    // the group, so that it can be registered again.
+    drop(buf_ring);
+    ring.register_buf_ring(4, 64, 1).unwrap();
+}
+
+#[test]
+fn test_multishot_recv() {
+    let ring = Uring::new(8).unwrap();
+    let buf_ring = ring.register_buf_ring(4, 64, 1).unwrap();
+    let (a, b) = UnixDatagram::pair().unwrap();
+
+    let mut handle = ring
+        .prepare_multishot_recv(Sqe::multishot_recv(b.as_raw_fd(), &buf_ring, 0))
+        .unwrap();
+    ring.submit().unwrap();
+
+    let msgs = [&b"one"[..], &b"two"[..], &b"three"[..]];
+    for msg in msgs {
+        a.send(msg).unwrap();
+    }
+    for msg in msgs {
+        let result = handle.wait().unwrap().unwrap();
+        assert!(result.more());
+        assert_eq!(result.as_io_result().unwrap(), msg.len());
+        assert!(result.buffer_id().unwrap() < 4);
+        assert_eq!(buf_ring.get(&result).unwrap(), msg);
+        buf_ring.recycle(result);
+    }
+
+    // Dropping the handle cancels the armed recv.
+    drop(handle);
+}
+
+#[test]
+fn test_recv_would_block() {
+    let ring = Uring::new(8).unwrap();
+    let buf_ring = ring.register_buf_ring(4, 64, 1).unwrap();
+    let (mut a, b) = UnixStream::pair().unwrap();
+
+    let handle = ring
+        .prepare_recv(Sqe::recv(b.as_raw_fd(), &buf_ring, libc::MSG_DONTWAIT))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(result.would_block());
+    assert!(result.buffer_id().is_none());
+
+    a.write_all(b"hello").unwrap();
+    let handle = ring
+        .prepare_recv(Sqe::recv(b.as_raw_fd(), &buf_ring, libc::MSG_DONTWAIT))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert!(!result.would_block());
+    assert_eq!(buf_ring.get(&result).unwrap(), b"hello");
+    buf_ring.recycle(result);
+}
@@ -0,0 +1,48 @@
+use aluring::{Error, Uring};
+
+#[test]
+fn test_register_buffers() {
+    let ring = Uring::new(8).unwrap();
+    assert_eq!(ring.registered_buffer_count().unwrap(), 0);
+    assert!(!ring.is_registered_buffer(0).unwrap());
+
+    ring.register_buffers((0..4).map(|_| vec![0; 4096]).collect())
+        .unwrap();
+    assert_eq!(ring.registered_buffer_count().unwrap(), 4);
+    assert!(ring.is_registered_buffer(0).unwrap());
+    assert!(ring.is_registered_buffer(3).unwrap());
+    assert!(!ring.is_registered_buffer(4).unwrap());
+}
+
+#[test]
+fn test_register_buffers_twice() {
+    let ring = Uring::new(8).unwrap();
+    ring.register_buffers(vec![vec![0; 4096]]).unwrap();
+    let err = ring.register_buffers(vec![vec![0; 4096]]).unwrap_err();
+    match err {
+        Error::RegisterBuffersError(e) => assert_eq!(e.raw_os_error(), Some(libc::EBUSY)),
+        e => panic!("unexpected error: {}", e),
+    }
+    assert_eq!(ring.registered_buffer_count().unwrap(), 1);
+}
+
+#[test]
+fn test_iowq_max_workers() {
+    let ring = Uring::new(8).unwrap();
+    let (bounded, unbounded) = match ring.iowq_max_workers() {
+        Ok(limits) => limits,
+        // Linux before 5.15.
+        Err(Error::RegisterIowqMaxWorkersError(e)) if e.raw_os_error() == Some(libc::EINVAL) => {
+            return
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+    };
+    assert!(bounded > 0 && unbounded > 0);
+
+    ring.set_iowq_max_workers(2, 3).unwrap();
+    assert_eq!(ring.iowq_max_workers().unwrap(), (2, 3));
+
+    // 0 keeps the current limit.
+    ring.set_iowq_max_workers(0, 4).unwrap();
+    assert_eq!(ring.iowq_max_workers().unwrap(), (2, 4));
+}
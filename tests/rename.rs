@@ -0,0 +1,54 @@
+use std::{ffi::CString, fs, os::unix::ffi::OsStrExt, path::Path};
+
+use aluring::{result::IoResult, sqe::Sqe, Uring};
+
+fn cstring(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap()
+}
+
+#[test]
+fn test_rename() {
+    let ring = Uring::new(8).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old");
+    let new = dir.path().join("new");
+    fs::write(&old, b"hello").unwrap();
+
+    let handle = ring
+        .prepare_rename(Sqe::rename(
+            libc::AT_FDCWD,
+            cstring(&old),
+            libc::AT_FDCWD,
+            cstring(&new),
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+    assert!(!old.exists());
+    assert_eq!(fs::read(&new).unwrap(), b"hello");
+}
+
+#[test]
+fn test_rename_noreplace() {
+    let ring = Uring::new(8).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old");
+    let new = dir.path().join("new");
+    fs::write(&old, b"old").unwrap();
+    fs::write(&new, b"new").unwrap();
+
+    let handle = ring
+        .prepare_rename(Sqe::rename(
+            libc::AT_FDCWD,
+            cstring(&old),
+            libc::AT_FDCWD,
+            cstring(&new),
+            libc::RENAME_NOREPLACE,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let err = handle.wait().unwrap().as_io_result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EEXIST));
+    assert_eq!(fs::read(&new).unwrap(), b"new");
+}
@@ -0,0 +1,97 @@
+use std::{io::Write, os::unix::io::AsRawFd};
+
+use aluring::{
+    buf::{UringBuf, UringWriteBuf},
+    handle::UringHandle,
+    result::{BufIoResult, IoResult, UringResult},
+    sqe::Sqe,
+    Uring,
+};
+
+#[test]
+fn test_uring_result_accessors() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"hello").unwrap();
+    let g = tempfile::tempfile().unwrap();
+
+    let handles: Vec<UringHandle> = vec![
+        ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0).tag(1))
+            .unwrap()
+            .into(),
+        ring.prepare_write(Sqe::write(
+            g.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(&b"world"[..]) },
+            0,
+        ))
+        .unwrap()
+        .into(),
+        ring.prepare_nop(Sqe::nop()).unwrap().into(),
+        // No file is registered, so the read fails.
+        ring.prepare_read(Sqe::read(0, UringBuf::Vec(vec![0; 16]), 0).fixed_file())
+            .unwrap()
+            .into(),
+    ];
+    ring.submit().unwrap();
+    let results = ring.wait_all(handles).unwrap();
+
+    let read = &results[0];
+    assert!(read.as_read().is_some());
+    assert!(read.as_write().is_none());
+    assert_eq!(read.bytes_transferred(), Some(5));
+    assert_eq!(read.tag(), Some(1));
+
+    let write = &results[1];
+    assert!(write.as_write().is_some());
+    assert!(write.as_read().is_none());
+    assert_eq!(write.bytes_transferred(), Some(5));
+    assert_eq!(write.tag(), None);
+
+    let nop = &results[2];
+    assert!(nop.as_nop().is_some());
+    assert_eq!(nop.bytes_transferred(), None);
+    assert_eq!(nop.flags(), 0);
+
+    // A failed read transferred nothing.
+    let failed = &results[3];
+    assert!(failed.as_read().is_some());
+    assert_eq!(failed.bytes_transferred(), None);
+}
+
+#[test]
+fn test_wait_handles() {
+    let ring = Uring::new(8).unwrap();
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"hello").unwrap();
+    let g = tempfile::tempfile().unwrap();
+
+    let handles = vec![
+        ring.prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 5]), 0))
+            .unwrap()
+            .into(),
+        ring.prepare_write(Sqe::write(
+            g.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(&b"world"[..]) },
+            0,
+        ))
+        .unwrap()
+        .into(),
+        ring.prepare_fsync(Sqe::fsync(f.as_raw_fd()))
+            .unwrap()
+            .into(),
+    ];
+    let results = ring.wait_handles(handles).unwrap();
+    assert_eq!(results.len(), 3);
+    match &results[..] {
+        [UringResult::Read(read), UringResult::Write(write), UringResult::Fsync(fsync)] => {
+            assert_eq!(read.as_io_result().unwrap(), 5);
+            assert_eq!(write.as_io_result().unwrap(), 5);
+            fsync.as_io_result().unwrap();
+        }
+        results => panic!("unexpected results: {:?}", results),
+    }
+    match results.into_iter().next() {
+        Some(UringResult::Read(read)) => assert_eq!(read.into_buf().as_slice(), b"hello"),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    io::Read,
+    net::{TcpListener, TcpStream},
+    os::unix::io::AsRawFd,
+};
+
+use aluring::{
+    buf::UringBuf,
+    probe::OpKind,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    Error, Uring,
+};
+
+fn tcp_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    (client, server)
+}
+
+#[test]
+fn test_send_zc() {
+    let ring = Uring::new(8).unwrap();
+    let (client, mut server) = tcp_pair();
+    if !ring.probe().unwrap().supports(OpKind::SendZc) {
+        let sqe = Sqe::send_zc(client.as_raw_fd(), UringBuf::Vec(vec![]), 0, 0);
+        assert!(matches!(
+            ring.prepare_send_zc(sqe),
+            Err(Error::Unsupported(OpKind::SendZc))
+        ));
+        return;
+    }
+
+    let h = ring
+        .prepare_send_zc(Sqe::send_zc(
+            client.as_raw_fd(),
+            UringBuf::Vec(b"hello".to_vec()),
+            libc::MSG_NOSIGNAL,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = h.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 5);
+    // Both the result and the notification are observed before the buffer is returned.
+    assert_eq!(ring.inflight().unwrap(), 0);
+    assert_eq!(result.into_buf().as_slice(), b"hello");
+
+    let mut buf = [0; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn test_send_zc_drop_handle() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::SendZc) {
+        return;
+    }
+    let (client, mut server) = tcp_pair();
+
+    let h = ring
+        .prepare_send_zc(Sqe::send_zc(
+            client.as_raw_fd(),
+            UringBuf::Vec(b"hello".to_vec()),
+            libc::MSG_NOSIGNAL,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    // The ring keeps the buffer until the notification arrives.
+    drop(h);
+    ring.drain().unwrap();
+    assert_eq!(ring.inflight().unwrap(), 0);
+
+    let mut buf = [0; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
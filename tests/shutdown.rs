@@ -0,0 +1,43 @@
+use std::{io::Read, os::unix::net::UnixStream, os::unix::prelude::AsRawFd};
+
+use aluring::{buf::UringWriteBuf, result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_shutdown_write() {
+    let ring = Uring::new(8).unwrap();
+    let (a, mut b) = UnixStream::pair().unwrap();
+    let handle = ring
+        .prepare_shutdown(Sqe::shutdown(a.as_raw_fd(), libc::SHUT_WR))
+        .unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+
+    let mut buf = [0u8; 16];
+    assert_eq!(b.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_linked_write_then_shutdown() {
+    let ring = Uring::new(8).unwrap();
+    let (a, mut b) = UnixStream::pair().unwrap();
+    let write = ring
+        .prepare_write(
+            Sqe::write(
+                a.as_raw_fd(),
+                unsafe { UringWriteBuf::from_slice(&b"bye"[..]) },
+                0,
+            )
+            .link(),
+        )
+        .unwrap();
+    let shutdown = ring
+        .prepare_shutdown(Sqe::shutdown(a.as_raw_fd(), libc::SHUT_WR))
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert_eq!(write.wait().unwrap().as_io_result().unwrap(), 3);
+    assert!(shutdown.wait().unwrap().as_io_result().is_ok());
+
+    let mut buf = Vec::new();
+    b.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"bye");
+}
@@ -0,0 +1,54 @@
+use std::{
+    fs::File,
+    io::Write,
+    os::unix::io::{AsRawFd, FromRawFd},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+use aluring::{buf::UringBuf, result::IoResult, sqe::Sqe, Uring};
+
+static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn on_signal(_: libc::c_int) {
+    SIGNALS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn test_wait_retries_on_eintr() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_signal as libc::sighandler_t;
+        // Without SA_RESTART so that the wait is interrupted with EINTR.
+        action.sa_flags = 0;
+        libc::sigemptyset(&mut action.sa_mask);
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (r, mut w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+
+    let ring = Uring::new(8).unwrap();
+    let handle = ring
+        .prepare_read(Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0))
+        .unwrap();
+    ring.submit().unwrap();
+
+    let waiter = unsafe { libc::pthread_self() };
+    let signaller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        unsafe { libc::pthread_kill(waiter, libc::SIGUSR1) };
+        thread::sleep(Duration::from_millis(50));
+        w.write_all(b"hello").unwrap();
+    });
+
+    let result = handle.wait().unwrap();
+    signaller.join().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), 5);
+    assert_eq!(SIGNALS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,141 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    mem,
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    os::unix::io::{AsRawFd, FromRawFd},
+};
+
+use aluring::{probe::OpKind, result::IoResult, sqe::Sqe, Uring};
+
+#[test]
+fn test_socket_and_close() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::Socket) {
+        return;
+    }
+
+    let handle = ring
+        .prepare_socket(Sqe::socket(libc::AF_INET, libc::SOCK_STREAM, 0, 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let fd = handle.wait().unwrap().as_io_result().unwrap();
+
+    let mut ty = 0i32;
+    let mut len = mem::size_of::<i32>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TYPE,
+            &mut ty as *mut i32 as *mut _,
+            &mut len,
+        )
+    };
+    assert_eq!(ret, 0);
+    assert_eq!(ty, libc::SOCK_STREAM);
+
+    let handle = ring.prepare_close(Sqe::close(fd)).unwrap();
+    ring.submit().unwrap();
+    assert!(handle.wait().unwrap().as_io_result().is_ok());
+    assert_eq!(unsafe { libc::fcntl(fd, libc::F_GETFD) }, -1);
+}
+
+#[test]
+fn test_bind_and_listen() {
+    let ring = Uring::new(8).unwrap();
+    let probe = ring.probe().unwrap();
+    if !probe.supports(OpKind::Bind) || !probe.supports(OpKind::Listen) {
+        return;
+    }
+    let socket = unsafe {
+        File::from_raw_fd(libc::socket(
+            libc::AF_INET,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+            0,
+        ))
+    };
+
+    let bind = ring
+        .prepare_bind(Sqe::bind(socket.as_raw_fd(), "127.0.0.1:0".parse().unwrap()).link())
+        .unwrap();
+    let listen = ring
+        .prepare_listen(Sqe::listen(socket.as_raw_fd(), 16))
+        .unwrap();
+    ring.submit().unwrap();
+    bind.wait().unwrap().as_io_result().unwrap();
+    listen.wait().unwrap().as_io_result().unwrap();
+
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockname(
+            socket.as_raw_fd(),
+            &mut addr as *mut libc::sockaddr_in as *mut _,
+            &mut len,
+        )
+    };
+    assert_eq!(ret, 0);
+    let port = u16::from_be(addr.sin_port);
+    assert_ne!(port, 0);
+    TcpStream::connect(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).unwrap();
+}
+
+#[test]
+fn test_loopback_server() {
+    let ring = Uring::new(8).unwrap();
+    let probe = ring.probe().unwrap();
+    if [OpKind::Socket, OpKind::Bind, OpKind::Listen]
+        .iter()
+        .any(|op| !probe.supports(*op))
+    {
+        return;
+    }
+
+    let handle = ring
+        .prepare_socket(Sqe::socket(
+            libc::AF_INET,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+            0,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let socket = handle.wait().unwrap().into_owned_fd().unwrap();
+
+    let bind = ring
+        .prepare_bind(Sqe::bind(socket.as_raw_fd(), "127.0.0.1:0".parse().unwrap()).link())
+        .unwrap();
+    let listen = ring
+        .prepare_listen(Sqe::listen(socket.as_raw_fd(), 16))
+        .unwrap();
+    ring.submit().unwrap();
+    bind.wait().unwrap().as_io_result().unwrap();
+    listen.wait().unwrap().as_io_result().unwrap();
+
+    let listener = TcpListener::from(socket);
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    client.write_all(b"hello").unwrap();
+    let mut buf = [0; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn test_socket_direct() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::Socket) || ring.register_files_sparse(4).is_err() {
+        return;
+    }
+
+    let handle = ring
+        .prepare_socket(Sqe::socket_direct(libc::AF_INET, libc::SOCK_STREAM, 0, 0))
+        .unwrap();
+    ring.submit().unwrap();
+    match handle.wait().unwrap().as_io_result() {
+        Ok(index) => assert!((0..4).contains(&index)),
+        // Direct sockets need Linux 5.19.
+        Err(e) => assert_eq!(e.raw_os_error(), Some(libc::EINVAL)),
+    }
+}
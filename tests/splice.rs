@@ -0,0 +1,57 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    os::unix::io::{AsRawFd, FromRawFd},
+};
+
+use aluring::{result::IoResult, sqe::Sqe, Uring};
+
+fn pipe() -> (File, File) {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) }
+}
+
+#[test]
+fn test_splice_pipe_to_file() {
+    let ring = Uring::new(8).unwrap();
+    let (r, mut w) = pipe();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    w.write_all(b"hello").unwrap();
+
+    let handle = ring
+        .prepare_splice(Sqe::splice(
+            r.as_raw_fd(),
+            None,
+            f.as_raw_fd(),
+            Some(3),
+            5,
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    assert_eq!(handle.wait().unwrap().as_io_result().unwrap(), 5);
+    assert_eq!(std::fs::read(f.path()).unwrap(), b"\0\0\0hello");
+}
+
+#[test]
+fn test_tee() {
+    let ring = Uring::new(8).unwrap();
+    let (mut r1, mut w1) = pipe();
+    let (mut r2, w2) = pipe();
+    w1.write_all(b"hello").unwrap();
+    drop(w1);
+
+    let handle = ring
+        .prepare_tee(Sqe::tee(r1.as_raw_fd(), w2.as_raw_fd(), 5, 0))
+        .unwrap();
+    ring.submit().unwrap();
+    assert_eq!(handle.wait().unwrap().as_io_result().unwrap(), 5);
+    drop(w2);
+
+    let (mut s1, mut s2) = (String::new(), String::new());
+    r1.read_to_string(&mut s1).unwrap();
+    r2.read_to_string(&mut s2).unwrap();
+    assert_eq!(s1, "hello");
+    assert_eq!(s2, "hello");
+}
@@ -0,0 +1,41 @@
+use std::{io::Write, os::unix::io::AsRawFd, sync::Arc, thread};
+
+use aluring::{
+    buf::UringBuf,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    sync::SyncUring,
+};
+
+#[test]
+fn test_sync_uring() {
+    let ring = Arc::new(SyncUring::new(8).unwrap());
+    let mut f = tempfile::tempfile().unwrap();
+    f.write_all(b"0123456789").unwrap();
+    let f = Arc::new(f);
+
+    let threads = (0..4)
+        .map(|_| {
+            let (ring, f) = (ring.clone(), f.clone());
+            thread::spawn(move || {
+                for i in 0..10u64 {
+                    let ring = ring.lock();
+                    let handle = ring
+                        .prepare_read(Sqe::read(f.as_raw_fd(), UringBuf::Vec(vec![0; 1]), i))
+                        .unwrap();
+                    ring.submit().unwrap();
+                    let result = handle.wait().unwrap();
+                    assert_eq!(result.as_io_result().unwrap(), 1);
+                    assert_eq!(result.into_buf().as_slice(), &[b'0' + i as u8]);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let guard = ring.lock();
+    assert!(ring.try_lock().is_none());
+    assert_eq!(guard.inflight().unwrap(), 0);
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs::File,
+    io::Write,
+    os::unix::io::{AsRawFd, FromRawFd},
+    time::Duration,
+};
+
+use aluring::{buf::UringBuf, result::IoResult, sqe::Sqe, Error, SqFullPolicy, Uring};
+
+fn pipe() -> (File, File) {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) }
+}
+
+#[test]
+fn test_read_timed_out() {
+    let ring = Uring::new(8).unwrap();
+    let (r, _w) = pipe();
+    let handle = ring
+        .prepare_read_with_timeout(
+            Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0),
+            Duration::from_millis(50),
+        )
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    let err = handle.wait().unwrap().as_io_result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ECANCELED));
+}
+
+#[test]
+fn test_read_before_timeout() {
+    let ring = Uring::new(8).unwrap();
+    let (r, mut w) = pipe();
+    w.write_all(b"hello").unwrap();
+    let handle = ring
+        .prepare_read_with_timeout(
+            Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert_eq!(handle.wait().unwrap().as_io_result().unwrap(), 5);
+}
+
+#[test]
+fn test_read_with_timeout_nearly_full_sq() {
+    let ring = Uring::builder(4)
+        .sq_full_policy(SqFullPolicy::Error)
+        .build()
+        .unwrap();
+    let (r, mut w) = pipe();
+    let nops = (0..3)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    // One free entry is not enough for the read and its timeout; neither is prepared.
+    assert!(matches!(
+        ring.prepare_read_with_timeout(
+            Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0),
+            Duration::from_secs(10),
+        ),
+        Err(Error::SqFull)
+    ));
+    assert_eq!(ring.sq_ready(), 3);
+
+    assert_eq!(ring.submit().unwrap(), 3);
+    w.write_all(b"hello").unwrap();
+    let handle = ring
+        .prepare_read_with_timeout(
+            Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert_eq!(handle.wait().unwrap().as_io_result().unwrap(), 5);
+    for nop in nops {
+        assert!(nop.wait().unwrap().as_io_result().is_ok());
+    }
+}
+
+#[test]
+fn test_read_with_timeout_auto_submit() {
+    let ring = Uring::new(4).unwrap();
+    let (r, mut w) = pipe();
+    let nops = (0..3)
+        .map(|_| ring.prepare_nop(Sqe::nop()).unwrap())
+        .collect::<Vec<_>>();
+    w.write_all(b"hello").unwrap();
+    // The queued nops are submitted to make room for both SQEs.
+    let handle = ring
+        .prepare_read_with_timeout(
+            Sqe::read(r.as_raw_fd(), UringBuf::Vec(vec![0; 16]), 0),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+    assert_eq!(ring.sq_ready(), 2);
+    assert_eq!(ring.submit().unwrap(), 2);
+    assert_eq!(handle.wait().unwrap().as_io_result().unwrap(), 5);
+    for nop in nops {
+        assert!(nop.wait().unwrap().as_io_result().is_ok());
+    }
+}
@@ -0,0 +1,58 @@
+use aluring::{probe::OpKind, result::IoResult, sqe::Sqe, Error, Uring};
+
+#[test]
+fn test_waitid() {
+    let ring = Uring::new(8).unwrap();
+
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        unsafe { libc::_exit(7) };
+    }
+
+    let handle =
+        match ring.prepare_waitid(Sqe::waitid(libc::P_PID, pid as libc::id_t, libc::WEXITED)) {
+            Err(Error::Unsupported(OpKind::Waitid)) => {
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                return;
+            }
+            handle => handle.unwrap(),
+        };
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    result.as_io_result().unwrap();
+    assert_eq!(result.siginfo().si_code, libc::CLD_EXITED);
+    assert_eq!(result.pid(), pid);
+    assert_eq!(result.status(), 7);
+}
+
+#[test]
+fn test_waitid_killed() {
+    let ring = Uring::new(8).unwrap();
+
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        loop {
+            unsafe { libc::pause() };
+        }
+    }
+    assert_eq!(unsafe { libc::kill(pid, libc::SIGKILL) }, 0);
+
+    let handle =
+        match ring.prepare_waitid(Sqe::waitid(libc::P_PID, pid as libc::id_t, libc::WEXITED)) {
+            Err(Error::Unsupported(OpKind::Waitid)) => {
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                return;
+            }
+            handle => handle.unwrap(),
+        };
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    result.as_io_result().unwrap();
+    assert_eq!(result.siginfo().si_code, libc::CLD_KILLED);
+    assert_eq!(result.pid(), pid);
+    assert_eq!(result.status(), libc::SIGKILL);
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs::File,
+    io::Read,
+    os::unix::prelude::{AsRawFd, FromRawFd},
+};
+
+use aluring::{
+    buf::{UringBuf, UringWriteBuf},
+    result::IoResult,
+    sqe::Sqe,
+    Error, Uring,
+};
+
+#[test]
+fn test_write_slice() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let config = b"key = value\n";
+    let handle = ring
+        .prepare_write(Sqe::write(
+            f.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(&config[..]) },
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let len = handle.wait().unwrap().as_io_result().unwrap();
+    assert_eq!(len, config.len());
+    assert_eq!(std::fs::read(f.path()).unwrap(), config);
+}
+
+#[test]
+fn test_drop_write_slice_handle() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+    {
+        let config = b"key = value\n".to_vec();
+        let _handle = ring
+            .prepare_write(Sqe::write(
+                f.as_raw_fd(),
+                unsafe { UringWriteBuf::from_slice(config.as_slice()) },
+                0,
+            ))
+            .unwrap();
+        // Dropping the handle waits for the write before `config` is freed.
+    }
+    assert_eq!(std::fs::read(f.path()).unwrap(), b"key = value\n");
+}
+
+#[test]
+fn test_short_write() {
+    let ring = Uring::new(8).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(
+        unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) },
+        0
+    );
+    let (r, w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let capacity = unsafe { libc::fcntl(w.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) };
+    assert!(capacity > 0);
+
+    let data = vec![0xa; capacity as usize * 2];
+    let handle = ring
+        .prepare_write(Sqe::write(
+            w.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(data.as_slice()) },
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.transferred(), capacity as usize);
+    assert!(result.is_short());
+    // The result does not own the borrowed slice.
+    assert!(result.remaining().is_none());
+    drop(r);
+}
+
+#[test]
+fn test_write_all_pipe() {
+    let ring = Uring::new(8).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let (mut r, w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let capacity = unsafe { libc::fcntl(w.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) };
+    assert!(capacity > 0);
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).unwrap();
+        buf
+    });
+
+    let data = (0..capacity as usize * 16)
+        .map(|i| i as u8)
+        .collect::<Vec<_>>();
+    let handle = ring
+        .prepare_write_all(Sqe::write(
+            w.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(data.as_slice()) },
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.as_io_result().unwrap(), data.len());
+    drop(w);
+    assert_eq!(reader.join().unwrap(), data);
+}
+
+#[test]
+fn test_write_all_partial_error() {
+    let ring = Uring::new(8).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(
+        unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) },
+        0
+    );
+    let (_r, w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let capacity = unsafe { libc::fcntl(w.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) };
+    assert!(capacity > 0);
+
+    // Nobody reads, so the retry after the first short write fails with `EAGAIN`.
+    let data = vec![0xa; capacity as usize * 2];
+    let handle = ring
+        .prepare_write_all(Sqe::write(
+            w.as_raw_fd(),
+            unsafe { UringWriteBuf::from_slice(data.as_slice()) },
+            0,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    match handle.wait() {
+        Err(Error::WriteAllError { written, source }) => {
+            assert_eq!(written, capacity as usize);
+            assert_eq!(source.raw_os_error(), Some(libc::EAGAIN));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_write_remaining() {
+    let ring = Uring::new(8).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(
+        unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) },
+        0
+    );
+    let (mut r, w) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let capacity = unsafe { libc::fcntl(w.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) } as usize;
+
+    let data = (0..capacity + 100).map(|i| i as u8).collect::<Vec<_>>();
+    let handle = ring
+        .prepare_write(Sqe::write(w.as_raw_fd(), UringBuf::Vec(data.clone()), 0))
+        .unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.transferred(), capacity);
+
+    let mut received = vec![0; capacity];
+    r.read_exact(&mut received).unwrap();
+    let handle = ring.prepare_write(result.remaining().unwrap()).unwrap();
+    ring.submit().unwrap();
+    let result = handle.wait().unwrap();
+    assert_eq!(result.transferred(), 100);
+    assert!(result.remaining().is_none());
+
+    drop(w);
+    r.read_to_end(&mut received).unwrap();
+    assert_eq!(received, data);
+}
+
+#[test]
+fn test_write_detached() {
+    let ring = Uring::new(8).unwrap();
+    let f = tempfile::NamedTempFile::new().unwrap();
+
+    // More writes than SQ entries; the ring submits as the SQ fills up.
+    for i in 0..64u8 {
+        ring.write_detached(f.as_raw_fd(), UringBuf::Vec(vec![i; 16]), i as u64 * 16)
+            .unwrap();
+    }
+    ring.drain().unwrap();
+    assert_eq!(ring.inflight().unwrap(), 0);
+
+    let data = std::fs::read(f.path()).unwrap();
+    let expected = (0..64u8).flat_map(|i| vec![i; 16]).collect::<Vec<_>>();
+    assert_eq!(data, expected);
+}
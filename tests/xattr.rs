@@ -0,0 +1,73 @@
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+
+use aluring::{
+    buf::UringBuf,
+    probe::OpKind,
+    result::{BufIoResult, IoResult},
+    sqe::Sqe,
+    Uring,
+};
+
+fn cstring(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap()
+}
+
+#[test]
+fn test_xattr_round_trip() {
+    let ring = Uring::new(8).unwrap();
+    let probe = ring.probe().unwrap();
+    if !probe.supports(OpKind::Setxattr) || !probe.supports(OpKind::Getxattr) {
+        return;
+    }
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let name = CString::new("user.aluring").unwrap();
+
+    let handle = ring
+        .prepare_setxattr(Sqe::setxattr(
+            cstring(f.path()),
+            name.clone(),
+            UringBuf::Vec(b"hello".to_vec()),
+            libc::XATTR_CREATE,
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    if let Err(e) = handle.wait().unwrap().as_io_result() {
+        // The file system of the temporary directory may not support user xattrs.
+        assert_eq!(e.raw_os_error(), Some(libc::EOPNOTSUPP));
+        return;
+    }
+
+    let handle = ring
+        .prepare_getxattr(Sqe::getxattr(
+            cstring(f.path()),
+            name,
+            UringBuf::Vec(vec![0; 64]),
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let (res, buf) = handle.wait().unwrap().into_parts();
+    let len = res.unwrap();
+    assert_eq!(&buf.as_slice()[..len], b"hello");
+}
+
+#[test]
+fn test_getxattr_missing() {
+    let ring = Uring::new(8).unwrap();
+    if !ring.probe().unwrap().supports(OpKind::Getxattr) {
+        return;
+    }
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let handle = ring
+        .prepare_getxattr(Sqe::getxattr(
+            cstring(f.path()),
+            CString::new("user.missing").unwrap(),
+            UringBuf::Vec(vec![0; 64]),
+        ))
+        .unwrap();
+    ring.submit().unwrap();
+    let err = handle.wait().unwrap().as_io_result().unwrap_err();
+    assert!(matches!(
+        err.raw_os_error(),
+        Some(libc::ENODATA) | Some(libc::EOPNOTSUPP)
+    ));
+}